@@ -183,6 +183,19 @@ pub fn run_histogram_benchmarks(c: &mut Criterion) {
             },
         );
 
+        // NonAliased impl, 8 slices.
+        #[cfg(feature = "bench")]
+        group.bench_with_input(
+            BenchmarkId::new("portable/nonaliased_8slice", size),
+            &data,
+            |b, data| {
+                b.iter(|| {
+                    let mut histogram32 = Histogram32::default();
+                    histogram_nonaliased_8slice(black_box(data), &mut histogram32)
+                })
+            },
+        );
+
         // Memcpy
         group.bench_with_input(
             BenchmarkId::new("portable/memcpy", size),
@@ -0,0 +1,61 @@
+//! Reports which implementations the crate's entry points use on the current machine.
+//!
+//! The implementations are picked at runtime, based on CPU features (and crate features);
+//! logging them helps diagnose performance differences between machines.
+
+use crate::histogram::{batched_dispatch_kind, DispatchKind};
+use crate::match_estimator::{match_backend, MatchBackend};
+
+/// The implementations picked by the crate's default entry points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Backends {
+    /// Implementation used by [`histogram32_from_bytes`] for larger inputs.
+    ///
+    /// [`histogram32_from_bytes`]: crate::histogram::histogram32_from_bytes
+    pub histogram: DispatchKind,
+    /// Implementation used by [`estimate_num_lz_matches_fast`].
+    ///
+    /// [`estimate_num_lz_matches_fast`]: crate::match_estimator::estimate_num_lz_matches_fast
+    pub match_estimator: MatchBackend,
+}
+
+/// Returns the implementations the default entry points use on the current machine.
+///
+/// This is cheap to call; the CPU feature detection behind it is only done once per process
+/// (and cached by `std`).
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::backends::active_backends;
+///
+/// println!("lossless-transform-utils backends: {:?}", active_backends());
+/// ```
+pub fn active_backends() -> Backends {
+    Backends {
+        histogram: batched_dispatch_kind(),
+        match_estimator: match_backend(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reported_backends_are_valid_for_target() {
+        let backends = active_backends();
+        assert_eq!(backends, active_backends());
+
+        let x86 = cfg!(any(target_arch = "x86", target_arch = "x86_64"));
+        match backends.histogram {
+            DispatchKind::Portable => {}
+            DispatchKind::Bmi1 => assert!(x86),
+        }
+        match backends.match_estimator {
+            MatchBackend::Generic => {}
+            MatchBackend::Avx2 => assert!(x86 && cfg!(feature = "estimator-avx2")),
+            MatchBackend::Avx512 => assert!(x86 && cfg!(feature = "estimator-avx512")),
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//! AVX2 version of [`shannon_entropy_of_histogram32`], with a vectorized `log2`.
+//!
+//! [`shannon_entropy_of_histogram32`]: super::shannon_entropy_of_histogram32
+
+#[cfg(target_arch = "x86")]
+use core::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::*;
+#[cfg(feature = "std")]
+use std::is_x86_feature_detected;
+
+/// Whether the AVX2 implementation can run. Without `std`, only true if the crate was compiled
+/// with the `avx2` target feature enabled.
+#[inline(always)]
+pub(super) fn has_avx2() -> bool {
+    #[cfg(feature = "std")]
+    return is_x86_feature_detected!("avx2");
+    #[cfg(not(feature = "std"))]
+    return cfg!(target_feature = "avx2");
+}
+
+/// Calculates the Shannon entropy of 256 counts, 8 at a time, in 2 [`__m256d`] accumulators.
+///
+/// Unlike the scalar fast path, this doesn't require every byte value to occur; counts of 0
+/// are masked out of the sum, so it is used for all histograms.
+///
+/// # Accuracy
+///
+/// The vector [`log2`] is off by at most ~7e-13; since the probabilities sum to 1, the entropy
+/// is off by at most the same amount, plus a few ulps of rounding from the different summation
+/// order. The result is within 1e-11 bits of the scalar implementation.
+///
+/// # Safety
+///
+/// The CPU must support AVX2.
+#[target_feature(enable = "avx2")]
+#[inline(never)]
+pub(crate) unsafe fn shannon_entropy_of_histogram32_avx2(counter: &[u32; 256], total: f64) -> f64 {
+    let total = _mm256_set1_pd(total);
+    let zero = _mm256_setzero_pd();
+    let mut entropy0 = _mm256_setzero_pd();
+    let mut entropy1 = _mm256_setzero_pd();
+
+    for chunk in counter.chunks_exact(8) {
+        let counts0 = u32_to_f64(_mm_loadu_si128(chunk.as_ptr() as *const __m128i));
+        let counts1 = u32_to_f64(_mm_loadu_si128(chunk.as_ptr().add(4) as *const __m128i));
+
+        let p0 = _mm256_div_pd(counts0, total);
+        let p1 = _mm256_div_pd(counts1, total);
+
+        // Counts of 0 contribute nothing; `log2(0.0)` is finite here, but `0 / 0` is not.
+        let present0 = _mm256_cmp_pd(counts0, zero, _CMP_NEQ_OQ);
+        let present1 = _mm256_cmp_pd(counts1, zero, _CMP_NEQ_OQ);
+
+        let term0 = _mm256_and_pd(_mm256_mul_pd(p0, log2(p0)), present0);
+        let term1 = _mm256_and_pd(_mm256_mul_pd(p1, log2(p1)), present1);
+
+        entropy0 = _mm256_sub_pd(entropy0, term0);
+        entropy1 = _mm256_sub_pd(entropy1, term1);
+    }
+
+    let entropy = _mm256_add_pd(entropy0, entropy1);
+    let sum = _mm_add_pd(
+        _mm256_castpd256_pd128(entropy),
+        _mm256_extractf128_pd(entropy, 1),
+    );
+    _mm_cvtsd_f64(_mm_add_sd(sum, _mm_unpackhi_pd(sum, sum)))
+}
+
+/// Converts 4 [`u32`] values to [`f64`], exactly.
+///
+/// AVX2 only converts signed integers, so the values are biased into `i32` range and back.
+#[target_feature(enable = "avx2")]
+#[inline]
+unsafe fn u32_to_f64(values: __m128i) -> __m256d {
+    let biased = _mm_xor_si128(values, _mm_set1_epi32(i32::MIN));
+    _mm256_add_pd(_mm256_cvtepi32_pd(biased), _mm256_set1_pd(2147483648.0))
+}
+
+/// Vectorized `log2` for positive, normal inputs.
+///
+/// Splits `x` into `2^e * m`, with `m` in `[sqrt(0.5), sqrt(2))`, then evaluates
+/// `ln(m) = 2 * atanh((m - 1) / (m + 1))` with 7 terms of its odd power series. With
+/// `|t| <= 0.1716`, the first omitted term bounds the absolute error to ~7e-13.
+///
+/// Zero maps to -1023 rather than negative infinity; callers mask such lanes out.
+#[target_feature(enable = "avx2")]
+#[inline]
+unsafe fn log2(x: __m256d) -> __m256d {
+    const MANTISSA_MASK: i64 = 0x000F_FFFF_FFFF_FFFF;
+    const ONE_BITS: i64 = 0x3FF0_0000_0000_0000;
+    // 2^52, as a double; OR-ing a small integer into its mantissa adds it to 2^52.
+    const TWO_POW_52_BITS: i64 = 0x4330_0000_0000_0000;
+
+    let bits = _mm256_castpd_si256(x);
+
+    // Exponent, converted to a double via the 2^52 trick; AVX2 has no i64 to f64 conversion.
+    let exponent_bits = _mm256_or_si256(
+        _mm256_srli_epi64(bits, 52),
+        _mm256_set1_epi64x(TWO_POW_52_BITS),
+    );
+    let mut exponent = _mm256_sub_pd(
+        _mm256_castsi256_pd(exponent_bits),
+        _mm256_set1_pd(4503599627370496.0 + 1023.0),
+    );
+
+    // Mantissa in [1, 2), moved to [sqrt(0.5), sqrt(2)) to keep the series argument small.
+    let mut mantissa = _mm256_castsi256_pd(_mm256_or_si256(
+        _mm256_and_si256(bits, _mm256_set1_epi64x(MANTISSA_MASK)),
+        _mm256_set1_epi64x(ONE_BITS),
+    ));
+    let above = _mm256_cmp_pd(
+        mantissa,
+        _mm256_set1_pd(core::f64::consts::SQRT_2),
+        _CMP_GT_OQ,
+    );
+    mantissa = _mm256_blendv_pd(
+        mantissa,
+        _mm256_mul_pd(mantissa, _mm256_set1_pd(0.5)),
+        above,
+    );
+    exponent = _mm256_add_pd(exponent, _mm256_and_pd(above, _mm256_set1_pd(1.0)));
+
+    let one = _mm256_set1_pd(1.0);
+    let t = _mm256_div_pd(_mm256_sub_pd(mantissa, one), _mm256_add_pd(mantissa, one));
+    let t2 = _mm256_mul_pd(t, t);
+
+    // 2 * (1 + t^2/3 + t^4/5 + ... + t^12/13), via Horner's method.
+    let mut poly = _mm256_set1_pd(2.0 / 13.0);
+    for coefficient in [2.0 / 11.0, 2.0 / 9.0, 2.0 / 7.0, 2.0 / 5.0, 2.0 / 3.0, 2.0] {
+        poly = _mm256_add_pd(_mm256_mul_pd(poly, t2), _mm256_set1_pd(coefficient));
+    }
+    let ln_mantissa = _mm256_mul_pd(poly, t);
+
+    _mm256_add_pd(
+        exponent,
+        _mm256_mul_pd(ln_mantissa, _mm256_set1_pd(core::f64::consts::LOG2_E)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::shannon_entropy_of_counts;
+    use super::*;
+    use crate::test_utils::random_bytes;
+
+    fn random_counter(seed: u32, max: u32, zero_every: u32) -> [u32; 256] {
+        let random = random_bytes(1024, seed);
+        core::array::from_fn(|x| {
+            if zero_every != 0 && (x as u32).is_multiple_of(zero_every) {
+                return 0;
+            }
+            let chunk = &random[x * 4..x * 4 + 4];
+            u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]) % max + 1
+        })
+    }
+
+    #[test]
+    fn avx2_matches_scalar_on_random_histograms() {
+        if !has_avx2() {
+            return;
+        }
+
+        let mut seed = 12345;
+        for max in [1, 2, 100, 65536, u32::MAX / 256, u32::MAX - 1] {
+            for zero_every in [0, 1, 2, 7, 64] {
+                for _ in 0..64 {
+                    seed += 1;
+                    let counter = random_counter(seed, max, zero_every);
+                    let total = counter.iter().map(|&x| x as u64).sum::<u64>();
+
+                    let expected = shannon_entropy_of_counts(&counter, total);
+                    let actual =
+                        unsafe { shannon_entropy_of_histogram32_avx2(&counter, total as f64) };
+                    assert!(
+                        (actual - expected).abs() < 1e-11,
+                        "max: {max}, zero_every: {zero_every}, {actual} vs {expected}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn avx2_log2_is_accurate() {
+        if !has_avx2() {
+            return;
+        }
+
+        let mut x = 1e-12;
+        while x <= 1.0 {
+            let mut lanes = [0.0; 4];
+            unsafe {
+                let result = log2(_mm256_set1_pd(x));
+                _mm256_storeu_pd(lanes.as_mut_ptr(), result);
+            }
+            assert!(
+                (lanes[0] - x.log2()).abs() < 1e-12,
+                "log2({x}) = {}",
+                lanes[0]
+            );
+            x *= 1.0009765625;
+        }
+    }
+}
@@ -0,0 +1,74 @@
+//! Comparisons between histograms by their entropy.
+
+use super::code_length_of_histogram32_no_size;
+use crate::histogram::Histogram32;
+use core::cmp::Ordering;
+
+/// Compares two histograms by how compressible their data is, for picking between two
+/// candidate transforms of the same data.
+///
+/// The histograms are ordered by their order-0 entropy (bits per symbol), lowest first; so
+/// [`Ordering::Less`] means `a` is **more** compressible than `b`, and [`Ordering::Greater`]
+/// means `b` is. This suits [`slice::sort_by`], which then puts the most compressible
+/// candidate first.
+///
+/// Only the entropy is compared, not the total size; when the candidates differ in length,
+/// compare [`EntropyResult::total_bits`] instead.
+///
+/// # Returns
+///
+/// [`Ordering::Less`] if `a` has the lower entropy, [`Ordering::Greater`] if `b` does, and
+/// [`Ordering::Equal`] if both have the same entropy (e.g. both are empty).
+///
+/// # Example
+///
+/// ```
+/// use core::cmp::Ordering;
+/// use lossless_transform_utils::entropy::more_compressible;
+/// use lossless_transform_utils::histogram::Histogram32;
+///
+/// let skewed = Histogram32::from_bytes(&[0, 0, 0, 1]);
+/// let uniform = Histogram32::from_bytes(&[0, 1, 2, 3]);
+/// assert_eq!(more_compressible(&skewed, &uniform), Ordering::Less);
+/// ```
+///
+/// [`EntropyResult::total_bits`]: super::EntropyResult::total_bits
+pub fn more_compressible(a: &Histogram32, b: &Histogram32) -> Ordering {
+    let entropy_a = code_length_of_histogram32_no_size(a);
+    let entropy_b = code_length_of_histogram32_no_size(b);
+    entropy_a.total_cmp(&entropy_b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn skewed_is_more_compressible_than_uniform() {
+        let uniform_data: Vec<u8> = (0..=255).cycle().take(1 << 16).collect();
+        let skewed_data: Vec<u8> = (0..1 << 16_u32)
+            .map(|x| if x % 8 == 0 { (x / 8) as u8 } else { b'e' })
+            .collect();
+
+        let uniform = Histogram32::from_bytes(&uniform_data);
+        let skewed = Histogram32::from_bytes(&skewed_data);
+
+        assert_eq!(more_compressible(&skewed, &uniform), Ordering::Less);
+        assert_eq!(more_compressible(&uniform, &skewed), Ordering::Greater);
+        assert_eq!(more_compressible(&skewed, &skewed), Ordering::Equal);
+    }
+
+    #[test]
+    fn sorts_most_compressible_first() {
+        let mut candidates = [
+            Histogram32::from_bytes(&[0, 1, 2, 3]),
+            Histogram32::from_bytes(&[7; 4]),
+            Histogram32::from_bytes(&[0, 0, 1, 1]),
+        ];
+        candidates.sort_by(more_compressible);
+
+        let counts: Vec<u32> = candidates.iter().map(|h| h.counter[0]).collect();
+        assert_eq!(counts, [0, 2, 1]);
+    }
+}
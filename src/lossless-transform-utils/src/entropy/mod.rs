@@ -28,8 +28,21 @@
 //!
 //! However, because the input histograms only have 256 elements, the accuracy tradeoff for performance
 //! is considered worthwhile here.
+//!
+//! The exception is x86 CPUs with AVX2, where [`shannon_entropy_of_histogram32`] uses a vectorized
+//! polynomial `log2`; it stays within 1e-11 bits of the scalar result.
+
+use crate::histogram::{histogram32_from_bytes, Histogram32};
+use crate::math::{exp2, log2, log2f};
 
-use crate::histogram::Histogram32;
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod avx2;
+mod compare;
+pub use compare::*;
+mod reduced;
+pub use reduced::*;
+mod windows;
+pub use windows::*;
 
 /// Calculates the Shannon entropy of a [Histogram32] using floating point arithmetic.
 /// The entropy is the average number of bits needed to represent each symbol.
@@ -64,6 +77,8 @@ use crate::histogram::Histogram32;
 ///
 /// - This implementation prioritizes accuracy over performance for small histograms (256 elements).
 /// - For high-throughput scenarios, consider using more optimized methods if performance is critical.
+/// - On x86 CPUs with AVX2, a vectorized implementation is used; which approximates `log2` with
+///   a polynomial, and is within 1e-11 bits of the scalar one.
 pub fn shannon_entropy_of_histogram32(counter: &[u32; 256], total: u64) -> f64 {
     // Pseudocode for Shannon Entropy 'the proper way':
     //
@@ -75,14 +90,19 @@ pub fn shannon_entropy_of_histogram32(counter: &[u32; 256], total: u64) -> f64 {
     //    }
     // }
 
-    let total = total as f64;
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    if avx2::has_avx2() {
+        return unsafe { avx2::shannon_entropy_of_histogram32_avx2(counter, total as f64) };
+    }
+
     if counter.iter().all(|&x| x > 0) {
-        shannon_entropy_of_histogram32_fast(counter, total)
+        shannon_entropy_of_histogram32_fast(counter, total as f64)
     } else {
-        shannon_entropy_of_histogram32_slow(counter, total)
+        shannon_entropy_of_counts(counter, total)
     }
 }
 
+#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
 #[inline(always)]
 fn shannon_entropy_of_histogram32_fast(counter: &[u32; 256], total: f64) -> f64 {
     let mut entropy0 = 0.0;
@@ -96,33 +116,233 @@ fn shannon_entropy_of_histogram32_fast(counter: &[u32; 256], total: f64) -> f64
         let p2 = chunk[2] as f64 / total;
         let p3 = chunk[3] as f64 / total;
 
-        entropy0 -= p0 * p0.log2();
-        entropy1 -= p1 * p1.log2();
-        entropy2 -= p2 * p2.log2();
-        entropy3 -= p3 * p3.log2();
+        entropy0 -= p0 * log2(p0);
+        entropy1 -= p1 * log2(p1);
+        entropy2 -= p2 * log2(p2);
+        entropy3 -= p3 * log2(p3);
     }
 
     entropy0 + entropy1 + entropy2 + entropy3
 }
 
+/// WASM `simd128` version of the fast path.
+///
+/// There is no vector `log2`, so that part stays scalar; but the divisions, multiplications
+/// and accumulation run on `f64x2` lanes. The lanes hold the same 4 accumulators as the scalar
+/// version and are summed in the same order, so the results are identical.
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+#[inline(always)]
+fn shannon_entropy_of_histogram32_fast(counter: &[u32; 256], total: f64) -> f64 {
+    use core::arch::wasm32::*;
+
+    let total = f64x2_splat(total);
+    let mut entropy01 = f64x2_splat(0.0);
+    let mut entropy23 = f64x2_splat(0.0);
+
+    for chunk in counter.chunks_exact(4) {
+        let p01 = f64x2_div(f64x2(chunk[0] as f64, chunk[1] as f64), total);
+        let p23 = f64x2_div(f64x2(chunk[2] as f64, chunk[3] as f64), total);
+
+        let log01 = f64x2(
+            log2(f64x2_extract_lane::<0>(p01)),
+            log2(f64x2_extract_lane::<1>(p01)),
+        );
+        let log23 = f64x2(
+            log2(f64x2_extract_lane::<0>(p23)),
+            log2(f64x2_extract_lane::<1>(p23)),
+        );
+
+        entropy01 = f64x2_sub(entropy01, f64x2_mul(p01, log01));
+        entropy23 = f64x2_sub(entropy23, f64x2_mul(p23, log23));
+    }
+
+    f64x2_extract_lane::<0>(entropy01)
+        + f64x2_extract_lane::<1>(entropy01)
+        + f64x2_extract_lane::<0>(entropy23)
+        + f64x2_extract_lane::<1>(entropy23)
+}
+
+/// Calculates the Shannon entropy of a 256 element array of counts, for any counter width.
+///
+/// This is the same calculation as [`shannon_entropy_of_histogram32`], for histograms which
+/// don't use [`u32`] counters (e.g. [`u16`] for small blocks, or [`u64`] for very large
+/// inputs), without converting them to [`u32`] first.
+///
+/// # Arguments
+///
+/// * `counter` - The counts of each symbol
+/// * `total` - The total count of all symbols
+///
+/// # Returns
+///
+/// The Shannon entropy in bits. i.e. the average number of bits needed to represent each symbol
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::entropy::shannon_entropy_of_counts;
+///
+/// let mut counter = [0u16; 256];
+/// counter[0] = 2;
+/// counter[1] = 2;
+///
+/// assert_eq!(shannon_entropy_of_counts(&counter, 4), 1.0);
+/// ```
+///
+/// # Remarks
+///
+/// For [`u32`] counts, prefer [`shannon_entropy_of_histogram32`]; it has a faster path for
+/// histograms where every byte value occurs.
+pub fn shannon_entropy_of_counts<T: Into<u64> + Copy>(counter: &[T; 256], total: u64) -> f64 {
+    shannon_entropy_of_counts_with_log2(counter, total, log2)
+}
+
+/// Sums the entropy of each count in byte order, into a single accumulator, using `log2`.
 #[inline(always)]
-fn shannon_entropy_of_histogram32_slow(counter: &[u32; 256], total: f64) -> f64 {
+fn shannon_entropy_of_counts_with_log2<T: Into<u64> + Copy>(
+    counter: &[T; 256],
+    total: u64,
+    log2: fn(f64) -> f64,
+) -> f64 {
+    let total = total as f64;
     let mut entropy = 0.0;
-    for count in counter {
-        if *count == 0 {
+    for &count in counter {
+        let count: u64 = count.into();
+        if count == 0 {
             continue;
         }
-        let probability = *count as f64 / total;
-        let entropy_value = probability * probability.log2();
-        entropy -= entropy_value;
+        let probability = count as f64 / total;
+        entropy -= probability * log2(probability);
     }
     entropy
 }
 
+/// Calculates the Shannon entropy of a [Histogram32] with a fixed, strictly ordered summation.
+///
+/// [`shannon_entropy_of_histogram32`] splits the sum across four accumulators (and may take
+/// either the fast or slow path), so the low order bits of the result depend on the summation
+/// order. This variant always sums the 256 terms one by one, in byte order, into a single
+/// accumulator, and is kept out of line so it cannot be inlined and re-optimized differently
+/// per call site. The logarithm always comes from the pure Rust [`libm`] crate rather than the
+/// platform's math library, and Rust never contracts `a * b + c` into a fused multiply-add on
+/// its own; so the result is bit-identical across targets.
+///
+/// Use this for golden tests that compare entropy values across machines.
+///
+/// # Arguments
+///
+/// * `counter` - The counts of each symbol
+/// * `total` - The total count of all symbols
+///
+/// # Returns
+///
+/// The Shannon entropy in bits. i.e. the average number of bits needed to represent each symbol
+///
+/// # Performance
+///
+/// The single dependency chain makes this slightly slower than [`shannon_entropy_of_histogram32`];
+/// the difference is a few hundred nanoseconds per histogram at most.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::histogram::Histogram32;
+/// use lossless_transform_utils::entropy::shannon_entropy_of_histogram32_deterministic;
+///
+/// let histogram = Histogram32::from_bytes(&[0, 0, 1, 1]);
+/// let entropy = shannon_entropy_of_histogram32_deterministic(&histogram.counter, 4);
+/// assert_eq!(entropy, 1.0);
+/// ```
+#[inline(never)]
+pub fn shannon_entropy_of_histogram32_deterministic(counter: &[u32; 256], total: u64) -> f64 {
+    shannon_entropy_of_counts_with_log2(counter, total, libm::log2)
+}
+
+/// Calculates the Shannon entropy of a [Histogram32] using [`f32`] arithmetic.
+///
+/// Same as [`shannon_entropy_of_histogram32`], but the probabilities and logarithms are
+/// computed in single precision. For targets where double precision arithmetic is slow, or
+/// callers who compute the entropy of many histograms and only compare the results coarsely.
+///
+/// # Precision
+///
+/// Counts and the total above 2^24 are rounded when converted to [`f32`], and each term carries
+/// a relative error of ~1e-7; so the result is accurate to ~1e-5 bits, versus ~1e-12 for the
+/// [`f64`] version. That's plenty for picking between transforms, but not for telling apart
+/// inputs whose entropies differ by only a few bytes worth of bits.
+///
+/// # Arguments
+///
+/// * `counter` - The counts of each symbol
+/// * `total` - The total count of all symbols
+///
+/// # Returns
+///
+/// The Shannon entropy in bits. i.e. the average number of bits needed to represent each symbol
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::histogram::Histogram32;
+/// use lossless_transform_utils::entropy::shannon_entropy_of_histogram32_f32;
+///
+/// let histogram = Histogram32::from_bytes(&[0, 0, 1, 1]);
+/// assert_eq!(shannon_entropy_of_histogram32_f32(&histogram.counter, 4), 1.0);
+/// ```
+pub fn shannon_entropy_of_histogram32_f32(counter: &[u32; 256], total: u64) -> f32 {
+    let total = total as f32;
+    let mut entropy = [0.0_f32; 4];
+    for chunk in counter.chunks_exact(4) {
+        for (entropy, &count) in entropy.iter_mut().zip(chunk) {
+            if count > 0 {
+                let probability = count as f32 / total;
+                *entropy -= probability * log2f(probability);
+            }
+        }
+    }
+
+    entropy.iter().sum()
+}
+
+/// Calculates the entropy of a normalized (e.g. FSE/tANS) frequency table, in bits per symbol.
+///
+/// Each entry is treated as the probability `table[i] / (1 << table_log)`; i.e. this is the
+/// entropy of the quantized table, not of the data it was built from. Comparing the two
+/// gives the coding loss caused by normalization.
+///
+/// # Arguments
+///
+/// * `table` - The normalized frequencies, which should sum to `1 << table_log`.
+/// * `table_log` - The log2 of the table size.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::entropy::entropy_of_normalized_table;
+///
+/// let mut table = [0u16; 256];
+/// table[0] = 16;
+/// table[1] = 16;
+///
+/// assert_eq!(entropy_of_normalized_table(&table, 5), 1.0);
+/// ```
+pub fn entropy_of_normalized_table(table: &[u16; 256], table_log: u32) -> f64 {
+    shannon_entropy_of_counts(table, 1 << table_log)
+}
+
 /// Calculates the ideal code length in bits for a given histogram.
 /// This lets us estimate how compressible the data is during 'entropy coding' steps.
 ///
 /// See [`shannon_entropy_of_histogram32`] for more details; this is just a wrapper around that function.
+///
+/// # Precision
+///
+/// The total is summed as a [`u64`], so it cannot overflow (at most `256 * u32::MAX`, ~2^40).
+/// Both the counts and the total are below 2^53 and thus exact as [`f64`]; so each probability
+/// is only rounded once, by the division, with a relative error of ~1e-16. The entropy is
+/// therefore accurate to ~1e-12 bits even for histograms with counts near [`u32::MAX`]; this
+/// also holds for [`u64`] counts via [`shannon_entropy_of_counts`], where counts beyond 2^53
+/// add just one more rounding of the same size.
 pub fn code_length_of_histogram32_no_size(histogram: &Histogram32) -> f64 {
     let total: u64 = histogram.counter.iter().map(|&x| x as u64).sum();
     code_length_of_histogram32(histogram, total)
@@ -136,12 +356,127 @@ pub fn code_length_of_histogram32(histogram: &Histogram32, total: u64) -> f64 {
     shannon_entropy_of_histogram32(&histogram.counter, total)
 }
 
+/// Entropy of a histogram, along with the totals derived from it.
+///
+/// Returned by [`analyze_entropy`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EntropyResult {
+    /// The Shannon entropy; i.e. average number of bits needed to represent each symbol.
+    pub bits_per_symbol: f64,
+    /// The ideal number of bits needed to represent all symbols;
+    /// `bits_per_symbol * total_symbols`.
+    pub total_bits: f64,
+    /// The number of symbols counted in the histogram.
+    pub total_symbols: u64,
+}
+
+/// Calculates the entropy of a [Histogram32], along with the total number of symbols and the
+/// total number of bits an ideal entropy coder would need for them.
+///
+/// The total is summed from the histogram once, and used for everything; instead of once
+/// for the entropy and again for the total bits.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::histogram::Histogram32;
+/// use lossless_transform_utils::entropy::analyze_entropy;
+///
+/// let histogram = Histogram32::from_bytes(&[0, 0, 1, 1]);
+/// let result = analyze_entropy(&histogram);
+///
+/// assert_eq!(result.bits_per_symbol, 1.0);
+/// assert_eq!(result.total_bits, 4.0);
+/// assert_eq!(result.total_symbols, 4);
+/// ```
+pub fn analyze_entropy(histogram: &Histogram32) -> EntropyResult {
+    let total_symbols = histogram.total();
+    let bits_per_symbol = code_length_of_histogram32(histogram, total_symbols);
+    EntropyResult {
+        bits_per_symbol,
+        total_bits: bits_per_symbol * total_symbols as f64,
+        total_symbols,
+    }
+}
+
+/// Calculates the perplexity of a [Histogram32], i.e. `2^entropy`.
+///
+/// This is the 'effective alphabet size': the number of equally likely symbols which would have
+/// the same entropy. Data using all 256 byte values uniformly has a perplexity of 256, a single
+/// repeated byte has a perplexity of 1; so it is often easier to reason about than bits.
+///
+/// # Returns
+///
+/// A value in `1.0..=256.0`. An empty histogram has an entropy of 0, so its perplexity is 1.0.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::histogram::Histogram32;
+/// use lossless_transform_utils::entropy::perplexity;
+///
+/// let histogram = Histogram32::from_bytes(&[0, 1, 2, 3]);
+/// assert_eq!(perplexity(&histogram), 4.0);
+/// ```
+pub fn perplexity(histogram: &Histogram32) -> f64 {
+    exp2(code_length_of_histogram32_no_size(histogram))
+}
+
+/// Calculates the Shannon entropy of a byte slice, i.e. the average number of bits per byte an
+/// ideal order-0 entropy coder would need.
+///
+/// This is a shortcut for building a [Histogram32] and passing it to
+/// [`code_length_of_histogram32`].
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::entropy::entropy_of_bytes;
+///
+/// assert_eq!(entropy_of_bytes(&[0, 1, 2, 3]), 2.0);
+/// ```
+pub fn entropy_of_bytes(bytes: &[u8]) -> f64 {
+    let histogram = Histogram32::from_bytes(bytes);
+    code_length_of_histogram32(&histogram, bytes.len() as u64)
+}
+
+/// Calculates the Shannon entropy of data arriving in chunks, without assembling the full input.
+///
+/// The histogram is built incrementally, one chunk at a time, so only the current chunk needs
+/// to be in memory. The result is the same as [`entropy_of_bytes`] over the concatenation of
+/// all chunks.
+///
+/// # Remarks
+///
+/// This still needs the 1KiB [Histogram32] (on the stack); that is unavoidable for order-0
+/// entropy, as any byte value may appear in a later chunk.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::entropy::streaming_entropy;
+///
+/// let chunks: [&[u8]; 2] = [&[0, 1], &[2, 3]];
+/// assert_eq!(streaming_entropy(chunks.into_iter()), 2.0);
+/// ```
+pub fn streaming_entropy<'a>(chunks: impl Iterator<Item = &'a [u8]>) -> f64 {
+    let mut histogram = Histogram32::default();
+    let mut total = 0_u64;
+    for chunk in chunks {
+        histogram32_from_bytes(chunk, &mut histogram);
+        total += chunk.len() as u64;
+    }
+
+    code_length_of_histogram32(&histogram, total)
+}
+
 #[cfg(test)]
 mod tests {
     use std::vec::Vec;
 
     use super::*;
-    use crate::histogram::Histogram32;
+    use crate::histogram::{Histogram, Histogram32};
+    use crate::test_utils::random_bytes;
 
     #[test]
     fn with_uniform_distribution() {
@@ -183,12 +518,57 @@ mod tests {
         assert!((code_length_of_histogram32(&hist, total) - expected).abs() < 1e-10);
     }
 
+    #[test]
+    fn perplexity_of_uniform_and_single_symbol() {
+        let uniform: Vec<u8> = (0..=255).cycle().take(256 * 16).collect();
+        let perplexity_uniform = perplexity(&Histogram32::from_bytes(&uniform));
+        assert!((perplexity_uniform - 256.0).abs() < 1e-9);
+
+        assert_eq!(perplexity(&Histogram32::from_bytes(&[7; 100])), 1.0);
+        assert_eq!(perplexity(&Histogram32::default()), 1.0);
+    }
+
     #[test]
     fn with_empty_histogram() {
         let hist = Histogram32::from_bytes(&[]);
         assert!((code_length_of_histogram32(&hist, 0) - 0.0).abs() < 1e-10);
     }
 
+    #[test]
+    fn entropy_stays_accurate_at_huge_counts() {
+        // Every byte present (fast path), and only some present (slow path).
+        let dense: [u32; 256] = core::array::from_fn(|x| (x % 17 + 1) as u32);
+        let sparse: [u32; 256] = core::array::from_fn(|x| (x % 5 == 0) as u32 * (x as u32 + 1));
+
+        for counter in [dense, sparse] {
+            let small = Histogram { counter };
+            let expected = code_length_of_histogram32_no_size(&Histogram32 { inner: small });
+
+            // Scale so the largest count is close to u32::MAX; total is ~2^40.
+            let max = *counter.iter().max().unwrap();
+            let factor = u32::MAX / max;
+            let scaled = Histogram32 {
+                inner: Histogram {
+                    counter: counter.map(|x| x * factor),
+                },
+            };
+            let entropy = code_length_of_histogram32_no_size(&scaled);
+            assert!(
+                (entropy - expected).abs() < 1e-12,
+                "{entropy} vs {expected}"
+            );
+
+            // u64 counts well beyond 2^53.
+            let huge = counter.map(|x| x as u64 * 3_u64.pow(32));
+            let total = huge.iter().sum();
+            let entropy = shannon_entropy_of_counts(&huge, total);
+            assert!(
+                (entropy - expected).abs() < 1e-12,
+                "{entropy} vs {expected}"
+            );
+        }
+    }
+
     #[test]
     fn code_length_no_size_equals_with_size() {
         let hist = Histogram32::from_bytes(&[0, 0, 0, 1]);
@@ -201,6 +581,105 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    fn simd128_fast_path_matches_scalar() {
+        let data: Vec<u8> = (0..10_000_u32).map(|x| (x * 33) as u8).collect();
+        let total = data.len() as u64;
+        let hist = Histogram32::from_bytes(&data);
+
+        let simd = shannon_entropy_of_histogram32_fast(&hist.counter, total as f64);
+        let scalar = shannon_entropy_of_counts(&hist.counter, total);
+
+        assert!(
+            (simd - scalar).abs() < 1e-10,
+            "simd128 mismatch: simd={simd} scalar={scalar}"
+        );
+    }
+
+    #[test]
+    fn counts_of_any_width_match_u32_entropy() {
+        let data: Vec<u8> = (0..10_000_u32).map(|x| (x * x % 199) as u8).collect();
+        let total = data.len() as u64;
+        let hist = Histogram32::from_bytes(&data);
+
+        let counts_u16: [u16; 256] = core::array::from_fn(|x| hist.counter[x] as u16);
+        let counts_u64: [u64; 256] = core::array::from_fn(|x| hist.counter[x] as u64);
+        let expected = shannon_entropy_of_histogram32(&hist.counter, total);
+
+        assert!((shannon_entropy_of_counts(&counts_u16, total) - expected).abs() < 1e-10);
+        assert!((shannon_entropy_of_counts(&counts_u64, total) - expected).abs() < 1e-10);
+        assert!((shannon_entropy_of_counts(&hist.counter, total) - expected).abs() < 1e-10);
+    }
+
+    #[test]
+    fn deterministic_is_stable_and_close_to_regular_path() {
+        let data: Vec<u8> = (0..10_000_u32).map(|x| (x * 7 % 251) as u8).collect();
+        let total = data.len() as u64;
+        let hist = Histogram32::from_bytes(&data);
+
+        let first = shannon_entropy_of_histogram32_deterministic(&hist.counter, total);
+        let second = shannon_entropy_of_histogram32_deterministic(&hist.counter, total);
+        let regular = shannon_entropy_of_histogram32(&hist.counter, total);
+
+        assert_eq!(first.to_bits(), second.to_bits());
+        // The regular path may use the AVX2 polynomial log2, which is only within 1e-11 bits.
+        assert!(
+            (first - regular).abs() < 1e-11,
+            "deterministic={first} regular={regular}"
+        );
+    }
+
+    #[test]
+    fn streaming_entropy_matches_entropy_of_concatenation() {
+        let data: Vec<u8> = (0..100_000_u64).map(|x| (x * x % 241) as u8).collect();
+        let expected = entropy_of_bytes(&data);
+
+        for chunk_size in [1, 63, 64, 4096, data.len()] {
+            let streamed = streaming_entropy(data.chunks(chunk_size));
+            assert!(
+                (streamed - expected).abs() < 1e-12,
+                "chunk size {chunk_size}: streamed={streamed} expected={expected}"
+            );
+        }
+
+        assert_eq!(streaming_entropy(core::iter::empty()), 0.0);
+    }
+
+    #[test]
+    fn proportional_normalized_table_matches_original_entropy() {
+        // Counts which sum to 4096, so they divide evenly into a 1 << 11 table.
+        let mut counts = [0u32; 256];
+        let mut table = [0u16; 256];
+        for (index, count) in [2048, 1024, 512, 256, 128, 64, 32, 32]
+            .into_iter()
+            .enumerate()
+        {
+            counts[index] = count;
+            table[index] = (count / 2) as u16;
+        }
+
+        let original = shannon_entropy_of_histogram32(&counts, 4096);
+        let normalized = entropy_of_normalized_table(&table, 11);
+        assert!((original - normalized).abs() < 1e-9);
+    }
+
+    #[test]
+    fn analyze_entropy_is_consistent() {
+        let data: Vec<u8> = (0..10_000_u32).map(|x| (x * 31 % 97) as u8).collect();
+        let result = analyze_entropy(&Histogram32::from_bytes(&data));
+
+        assert_eq!(result.total_symbols, data.len() as u64);
+        assert!((result.bits_per_symbol - entropy_of_bytes(&data)).abs() < 1e-12);
+        assert!(
+            (result.bits_per_symbol * result.total_symbols as f64 - result.total_bits).abs() < 1e-6
+        );
+
+        let empty = analyze_entropy(&Histogram32::default());
+        assert_eq!(empty.total_bits, 0.0);
+        assert_eq!(empty.total_symbols, 0);
+    }
+
     #[test]
     fn fast_path_matches_slow_path() {
         // Generate a large array of non-zero random bytes
@@ -213,11 +692,30 @@ mod tests {
 
         // Test non-zero case
         let fast = shannon_entropy_of_histogram32_fast(&hist.counter, total as f64);
-        let slow = shannon_entropy_of_histogram32_slow(&hist.counter, total as f64);
+        let slow = shannon_entropy_of_counts(&hist.counter, total);
 
         assert!(
             (fast - slow).abs() < 1e-10,
             "Non-zero case mismatch: fast={fast} slow={slow}"
         );
     }
+
+    #[test]
+    fn f32_entropy_is_close_to_f64() {
+        let skewed: Vec<u8> = random_bytes(1 << 17, 12345)
+            .chunks_exact(2)
+            .map(|x| x[0].min(x[1] >> 1))
+            .collect();
+
+        for data in [&skewed[..], &[7u8; 100], &[]] {
+            let hist = Histogram32::from_bytes(data);
+            let total = data.len() as u64;
+            let f64_entropy = shannon_entropy_of_histogram32(&hist.counter, total);
+            let f32_entropy = shannon_entropy_of_histogram32_f32(&hist.counter, total);
+            assert!(
+                (f32_entropy as f64 - f64_entropy).abs() < 1e-4,
+                "{f32_entropy} vs {f64_entropy}"
+            );
+        }
+    }
 }
@@ -0,0 +1,110 @@
+//! Entropy of a histogram after reducing its alphabet.
+
+use super::{shannon_entropy_of_counts, shannon_entropy_of_histogram32};
+use crate::histogram::Histogram32;
+
+/// Calculates the order-0 entropy of a histogram, after merging all byte values with a count
+/// below `min_count` into a single 'escape' symbol.
+///
+/// For estimating reduced-alphabet transforms; where rare bytes are coded as an escape followed
+/// by the literal byte. Merging only lowers the entropy, as fewer distinct symbols remain; the
+/// difference to [`code_length_of_histogram32_no_size`] is made up by the literals. By the chain
+/// rule, `full = merged + p_escape * tail_entropy`, where `tail_entropy` is the entropy of the
+/// rare bytes among themselves; so when the literals are stored as-is, at
+/// `log2(rare symbol count)` bits each, the total is never below the full entropy.
+///
+/// # Arguments
+///
+/// * `histogram` - The byte counts.
+/// * `min_count` - Byte values occurring fewer than this many times (but at least once) are
+///   merged. A `min_count` of 0 or 1 merges nothing.
+///
+/// # Returns
+///
+/// The entropy of the merged distribution, in bits per symbol; 0.0 for an empty histogram.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::entropy::merged_tail_entropy;
+/// use lossless_transform_utils::histogram::Histogram32;
+///
+/// // 0 and 1 are common; 2 to 5 merge into one escape symbol, taking half of the data.
+/// let histogram = Histogram32::from_bytes(&[0, 0, 1, 1, 2, 3, 4, 5]);
+/// assert_eq!(histogram.entropy(), 2.5);
+/// assert_eq!(merged_tail_entropy(&histogram, 2), 1.5);
+/// ```
+///
+/// [`code_length_of_histogram32_no_size`]: super::code_length_of_histogram32_no_size
+pub fn merged_tail_entropy(histogram: &Histogram32, min_count: u32) -> f64 {
+    // Widened, as the merged counts can add up to more than a `u32` holds.
+    let mut counter = histogram.counter.map(u64::from);
+    let mut escape_slot = None;
+    let mut escape_count = 0_u64;
+    for (byte, count) in counter.iter_mut().enumerate() {
+        if *count != 0 && *count < min_count as u64 {
+            escape_count += *count;
+            escape_slot.get_or_insert(byte);
+            *count = 0;
+        }
+    }
+
+    // Any merged byte's slot is now free, so the escape symbol can take the first one.
+    let Some(slot) = escape_slot else {
+        // Nothing merged; the distribution is unchanged.
+        return shannon_entropy_of_histogram32(&histogram.counter, histogram.total());
+    };
+    counter[slot] = escape_count;
+
+    shannon_entropy_of_counts(&counter, histogram.total())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn merging_long_tail_lowers_entropy_but_needs_literals() {
+        // A few very common bytes, and a long tail of 200 bytes occurring 1..=4 times each.
+        let mut data: Vec<u8> = Vec::new();
+        for byte in 0..8_u8 {
+            data.extend(core::iter::repeat_n(byte, 1000));
+        }
+        for byte in 8..208_u8 {
+            data.extend(core::iter::repeat_n(byte, byte as usize % 4 + 1));
+        }
+        let histogram = Histogram32::from_bytes(&data);
+
+        let full = histogram.entropy();
+        let merged = merged_tail_entropy(&histogram, 5);
+        assert!(merged < full, "{merged} vs {full}");
+
+        // Escapes, each followed by a literal of log2(200) bits.
+        let tail: u64 = (8..208).map(|x| x % 4 + 1).sum();
+        let escape_fraction = tail as f64 / data.len() as f64;
+        let with_literals = merged + escape_fraction * 200_f64.log2();
+        assert!(with_literals > full, "{with_literals} vs {full}");
+    }
+
+    #[test]
+    fn merging_nothing_keeps_entropy() {
+        let histogram = Histogram32::from_bytes(b"hello world");
+        for min_count in [0, 1] {
+            assert_eq!(
+                merged_tail_entropy(&histogram, min_count),
+                histogram.entropy()
+            );
+        }
+        assert_eq!(merged_tail_entropy(&Histogram32::default(), 10), 0.0);
+    }
+
+    #[test]
+    fn merged_counts_above_u32_max_do_not_overflow() {
+        // 4 bytes of 2^31 each merge into one escape symbol of 2^33; all the data is then one
+        // symbol.
+        let mut histogram = Histogram32::default();
+        histogram.counter[..4].fill(1 << 31);
+        assert_eq!(merged_tail_entropy(&histogram, u32::MAX), 0.0);
+    }
+}
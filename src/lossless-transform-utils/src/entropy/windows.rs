@@ -0,0 +1,154 @@
+//! Entropy over a sliding window, computed lazily without allocation.
+
+use super::code_length_of_histogram32;
+use crate::histogram::{histogram32_from_bytes, Histogram32};
+
+/// Lazily yields the entropy of each `window` byte window of the input, with consecutive
+/// windows starting `step` bytes apart.
+///
+/// No allocation is made; the only state is a single [Histogram32], which is updated
+/// incrementally as the window slides. So this works in `no_std`, and the caller can stop
+/// early with `.take()` or stream the values elsewhere.
+///
+/// # Arguments
+///
+/// * `bytes` - The input data.
+/// * `window` - Size of each window in bytes.
+/// * `step` - Distance in bytes between the starts of consecutive windows.
+///
+/// # Returns
+///
+/// An iterator over the entropy (bits per byte) of every full window, in order. Trailing bytes
+/// which don't fill a whole window are not yielded.
+///
+/// # Performance
+///
+/// O(n) total: each byte is added to the histogram once and removed once (when `step` is
+/// smaller than `window`), plus a 256 element pass to compute the entropy of each window.
+///
+/// # Panics
+///
+/// If `window` or `step` is 0.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::entropy::entropy_windows;
+///
+/// let mut data = [0u8; 512];
+/// data[256..].iter_mut().enumerate().for_each(|(x, byte)| *byte = x as u8);
+///
+/// let mut windows = entropy_windows(&data, 256, 256);
+/// assert_eq!(windows.next(), Some(0.0));
+/// assert!((windows.next().unwrap() - 8.0).abs() < 1e-9);
+/// assert_eq!(windows.next(), None);
+/// ```
+pub fn entropy_windows<'a>(
+    bytes: &'a [u8],
+    window: usize,
+    step: usize,
+) -> impl Iterator<Item = f64> + 'a {
+    assert!(window > 0, "window must be non-zero");
+    assert!(step > 0, "step must be non-zero");
+
+    EntropyWindows {
+        bytes,
+        window,
+        step,
+        start: 0,
+        histogram: None,
+    }
+}
+
+/// Iterator returned by [`entropy_windows`].
+struct EntropyWindows<'a> {
+    bytes: &'a [u8],
+    window: usize,
+    step: usize,
+    /// Start of the next window to yield.
+    start: usize,
+    /// Histogram of the previously yielded window; [`None`] before the first one.
+    histogram: Option<Histogram32>,
+}
+
+impl Iterator for EntropyWindows<'_> {
+    type Item = f64;
+
+    fn next(&mut self) -> Option<f64> {
+        let end = self.start.checked_add(self.window)?;
+        if end > self.bytes.len() {
+            return None;
+        }
+
+        let histogram = match &mut self.histogram {
+            // Overlaps the previous window; slide it instead of recounting.
+            Some(histogram) if self.step < self.window => {
+                for &byte in &self.bytes[self.start - self.step..self.start] {
+                    histogram.counter[byte as usize] -= 1;
+                }
+                histogram32_from_bytes(&self.bytes[end - self.step..end], histogram);
+                histogram
+            }
+            histogram => {
+                let histogram = histogram.insert(Histogram32::default());
+                histogram32_from_bytes(&self.bytes[self.start..end], histogram);
+                histogram
+            }
+        };
+
+        self.start = self.start.saturating_add(self.step);
+        Some(code_length_of_histogram32(histogram, self.window as u64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entropy::entropy_of_bytes;
+    use crate::test_utils::random_bytes;
+    use std::vec::Vec;
+
+    fn test_data() -> Vec<u8> {
+        random_bytes(10_000, 12345)
+            .iter()
+            .enumerate()
+            // Gets more random towards the end.
+            .map(|(x, &byte)| (byte as usize * x / 10_000) as u8)
+            .collect()
+    }
+
+    /// Entropy of each window, computed independently.
+    fn entropy_profile(bytes: &[u8], window: usize, step: usize) -> Vec<f64> {
+        (0..)
+            .map(|x| x * step)
+            .take_while(|&start| start + window <= bytes.len())
+            .map(|start| entropy_of_bytes(&bytes[start..start + window]))
+            .collect()
+    }
+
+    #[test]
+    fn windows_match_independent_entropy() {
+        let data = test_data();
+        for (window, step) in [
+            (1000, 1000),
+            (1000, 100),
+            (1000, 1),
+            (64, 1000),
+            (10_000, 7),
+        ] {
+            let lazy: Vec<f64> = entropy_windows(&data, window, step).collect();
+            let expected = entropy_profile(&data, window, step);
+            assert_eq!(lazy.len(), expected.len(), "{window}, {step}");
+
+            for (lazy, expected) in lazy.iter().zip(&expected) {
+                assert!((lazy - expected).abs() < 1e-12, "{window}, {step}");
+            }
+        }
+    }
+
+    #[test]
+    fn short_input_yields_nothing() {
+        assert_eq!(entropy_windows(&[1, 2, 3], 4, 1).count(), 0);
+        assert_eq!(entropy_windows(&[], 1, 1).count(), 0);
+    }
+}
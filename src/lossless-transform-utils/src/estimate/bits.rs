@@ -0,0 +1,159 @@
+//! Bitwise scans over the raw bytes; cheaper than building a histogram, when a histogram isn't
+//! otherwise needed.
+
+/// Returns how many low bit positions are ever set across all bytes in `bytes`.
+///
+/// All bytes are ORed together, and the position of the highest set bit is returned. A result
+/// below 8 means the top `8 - result` bits of every byte are always zero; e.g. 6-bit samples
+/// stored in bytes return 6, and could be bit-packed to 3/4 of their size before compression.
+///
+/// The bytes are ORed 32 at a time, as 4 independent [`u64`] accumulators, in the same batched
+/// style as the histogram; so this runs at memory speed.
+///
+/// # Returns
+///
+/// A value in `0..=8`; 0 if `bytes` is empty or all zero.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::estimate::estimate_bits_per_byte_used;
+///
+/// assert_eq!(estimate_bits_per_byte_used(&[0b0001, 0b0100, 0b0011]), 3);
+/// assert_eq!(estimate_bits_per_byte_used(&[0xFF]), 8);
+/// assert_eq!(estimate_bits_per_byte_used(&[]), 0);
+/// ```
+pub fn estimate_bits_per_byte_used(bytes: &[u8]) -> u8 {
+    let mut chunks = bytes.chunks_exact(32);
+    let mut combined0 = 0_u64;
+    let mut combined1 = 0_u64;
+    let mut combined2 = 0_u64;
+    let mut combined3 = 0_u64;
+
+    for chunk in &mut chunks {
+        combined0 |= u64::from_ne_bytes(chunk[0..8].try_into().unwrap());
+        combined1 |= u64::from_ne_bytes(chunk[8..16].try_into().unwrap());
+        combined2 |= u64::from_ne_bytes(chunk[16..24].try_into().unwrap());
+        combined3 |= u64::from_ne_bytes(chunk[24..32].try_into().unwrap());
+    }
+
+    // Fold the 8 byte lanes of the accumulators into a single byte.
+    let mut combined = combined0 | combined1 | combined2 | combined3;
+    combined |= combined >> 32;
+    combined |= combined >> 16;
+    combined |= combined >> 8;
+
+    let mut combined = combined as u8;
+    for &byte in chunks.remainder() {
+        combined |= byte;
+    }
+
+    (8 - combined.leading_zeros()) as u8
+}
+
+/// Default fraction of bytes `>= 0x80` above which [`contains_nul_or_high_bytes`] considers
+/// data binary.
+pub const DEFAULT_HIGH_BYTE_THRESHOLD: f64 = 0.3;
+
+/// Quick check for whether data is binary rather than text.
+///
+/// This is [`contains_nul_or_high_bytes_with_threshold`] with [`DEFAULT_HIGH_BYTE_THRESHOLD`].
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::estimate::contains_nul_or_high_bytes;
+///
+/// assert!(!contains_nul_or_high_bytes(b"Hello, world!\n"));
+/// assert!(contains_nul_or_high_bytes(&[0x7F, 0x45, 0x4C, 0x46, 0x02, 0x01, 0x01, 0x00]));
+/// ```
+pub fn contains_nul_or_high_bytes(bytes: &[u8]) -> bool {
+    contains_nul_or_high_bytes_with_threshold(bytes, DEFAULT_HIGH_BYTE_THRESHOLD)
+}
+
+/// Quick check for whether data is binary rather than text, e.g. as a gate before routing data
+/// to text specific transforms.
+///
+/// Data is considered binary if it contains any NUL byte, or more than `threshold` of its bytes
+/// are `>= 0x80`. Unlike [`looks_like_text`], this doesn't build a histogram; the bytes are read
+/// 8 at a time as a [`u64`], and the scan stops at the first NUL.
+///
+/// # Arguments
+///
+/// * `bytes` - The data to check.
+/// * `threshold` - Maximum fraction (`0.0..=1.0`) of bytes `>= 0x80` which is still considered
+///   text. UTF-8 text in non-Latin scripts is mostly such bytes; raise this to accept it.
+///
+/// # Returns
+///
+/// `true` if the data looks binary; `false` for text, or an empty input.
+///
+/// [`looks_like_text`]: super::looks_like_text
+pub fn contains_nul_or_high_bytes_with_threshold(bytes: &[u8], threshold: f64) -> bool {
+    const LOW_BITS: u64 = 0x0101_0101_0101_0101;
+    const HIGH_BITS: u64 = 0x8080_8080_8080_8080;
+
+    let mut chunks = bytes.chunks_exact(8);
+    let mut high_bytes = 0_usize;
+    for chunk in &mut chunks {
+        let value = u64::from_ne_bytes(chunk.try_into().unwrap());
+
+        // Non-zero if and only if any byte of `value` is zero.
+        if value.wrapping_sub(LOW_BITS) & !value & HIGH_BITS != 0 {
+            return true;
+        }
+        high_bytes += (value & HIGH_BITS).count_ones() as usize;
+    }
+
+    for &byte in chunks.remainder() {
+        if byte == 0 {
+            return true;
+        }
+        high_bytes += (byte >> 7) as usize;
+    }
+
+    high_bytes as f64 > threshold * bytes.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn six_bit_samples_use_six_bits() {
+        let data: Vec<u8> = (0..10_000_u32).map(|x| (x * 7 % 64) as u8).collect();
+        assert_eq!(estimate_bits_per_byte_used(&data), 6);
+    }
+
+    #[test]
+    fn high_bit_in_remainder_is_counted() {
+        let mut data = [1_u8; 35];
+        assert_eq!(estimate_bits_per_byte_used(&data), 1);
+
+        data[34] = 0x80;
+        assert_eq!(estimate_bits_per_byte_used(&data), 8);
+        assert_eq!(estimate_bits_per_byte_used(&[0; 64]), 0);
+    }
+
+    #[test]
+    fn ascii_text_is_not_binary() {
+        let text = b"The quick brown fox jumps over the lazy dog.\r\n\tSecond line; 1234567890!\n";
+        assert!(!contains_nul_or_high_bytes(&text.repeat(100)));
+        assert!(!contains_nul_or_high_bytes(&[]));
+    }
+
+    #[test]
+    fn embedded_nul_or_many_high_bytes_is_binary() {
+        for position in [0, 13, 799] {
+            let mut text = b"abcdefgh".repeat(100);
+            text[position] = 0;
+            assert!(contains_nul_or_high_bytes(&text), "NUL at {position}");
+        }
+
+        // 25% high bytes, as in accented UTF-8 text.
+        let text = b"abc\xC3".repeat(100);
+        assert!(!contains_nul_or_high_bytes(&text));
+        assert!(contains_nul_or_high_bytes_with_threshold(&text, 0.2));
+    }
+}
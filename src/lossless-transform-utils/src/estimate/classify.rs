@@ -0,0 +1,161 @@
+//! Scoring and coarse classification of how compressible a block is.
+
+use super::analyze_block;
+
+/// How compressible a block is, as classified by [`classify_compressibility`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Compressibility {
+    /// Expected savings below 5%; not worth compressing.
+    Incompressible,
+    /// Expected savings of 5% to 25%.
+    Low,
+    /// Expected savings of 25% to 60%.
+    Medium,
+    /// Expected savings of 60% or more.
+    High,
+}
+
+/// Minimum score for [`Compressibility::Low`].
+const LOW_THRESHOLD: f64 = 0.05;
+/// Minimum score for [`Compressibility::Medium`].
+const MEDIUM_THRESHOLD: f64 = 0.25;
+/// Minimum score for [`Compressibility::High`].
+const HIGH_THRESHOLD: f64 = 0.6;
+
+/// Weight of the entropy coding savings in [`compressibility_score`].
+const ENTROPY_WEIGHT: f64 = 0.5;
+/// Weight of the LZ savings in [`compressibility_score`].
+const LZ_WEIGHT: f64 = 1.0 - ENTROPY_WEIGHT;
+
+/// Scores how compressible a block is, as a single number in `0.0..=1.0`; higher is more
+/// compressible.
+///
+/// For ranking or routing many blocks by expected savings. The score is a weighted blend of
+/// two expected savings, each a fraction of the block's size:
+///
+/// - Entropy coding: `1 - order0_entropy_bits / 8`.
+/// - LZ: estimated matches per byte (see [`analyze_block`]); each match covers at least a byte.
+///
+/// ```text
+/// score = 0.5 * entropy_savings + 0.5 * lz_savings
+/// ```
+///
+/// The savings are averaged rather than added, as LZ and entropy coding partly exploit the same
+/// redundancy. Equal weights are a judgement call, not a measured fit; they rank data which
+/// benefits from both above data which benefits from only one. Random data scores near 0.0, a
+/// single repeated byte near 1.0.
+///
+/// # Returns
+///
+/// The score; 0.0 for an empty block.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::estimate::compressibility_score;
+///
+/// assert!(compressibility_score(&[0u8; 4096]) > 0.99);
+/// assert_eq!(compressibility_score(&[]), 0.0);
+/// ```
+pub fn compressibility_score(bytes: &[u8]) -> f64 {
+    if bytes.is_empty() {
+        return 0.0;
+    }
+
+    let metrics = analyze_block(bytes);
+    let entropy_savings = 1.0 - metrics.order0_entropy_bits / 8.0;
+    let lz_savings = metrics.estimated_matches as f64 / bytes.len() as f64;
+    (ENTROPY_WEIGHT * entropy_savings + LZ_WEIGHT * lz_savings).clamp(0.0, 1.0)
+}
+
+/// Classifies how compressible a block is into one of 4 coarse levels.
+///
+/// # Thresholds
+///
+/// The block is scored by [`compressibility_score`], and the score then maps to a level:
+///
+/// | Score          | Level                                 |
+/// |----------------|---------------------------------------|
+/// | `< 0.05`       | [`Compressibility::Incompressible`]   |
+/// | `0.05..0.25`   | [`Compressibility::Low`]              |
+/// | `0.25..0.6`    | [`Compressibility::Medium`]           |
+/// | `>= 0.6`       | [`Compressibility::High`]             |
+///
+/// An empty block is [`Compressibility::Incompressible`].
+///
+/// # Remarks
+///
+/// Like the score, this is a rough blend; data which only LZ or only entropy coding can shrink
+/// is rated below what that one method would save. Use it to triage blocks, e.g. to skip compressing incompressible ones, not to
+/// predict sizes.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::estimate::{classify_compressibility, Compressibility};
+///
+/// assert_eq!(classify_compressibility(&[0u8; 4096]), Compressibility::High);
+/// assert_eq!(classify_compressibility(&[]), Compressibility::Incompressible);
+/// ```
+pub fn classify_compressibility(bytes: &[u8]) -> Compressibility {
+    let score = compressibility_score(bytes);
+    if score >= HIGH_THRESHOLD {
+        Compressibility::High
+    } else if score >= MEDIUM_THRESHOLD {
+        Compressibility::Medium
+    } else if score >= LOW_THRESHOLD {
+        Compressibility::Low
+    } else {
+        Compressibility::Incompressible
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::random_bytes;
+    use std::vec::Vec;
+
+    /// Pseudo random bytes, limited to the lowest `bits` bits.
+    fn random_data(size: usize, bits: u32) -> Vec<u8> {
+        random_bytes(size, 12345)
+            .iter()
+            .map(|&x| x >> (8 - bits))
+            .collect()
+    }
+
+    #[test]
+    fn classifies_clear_cut_inputs() {
+        assert_eq!(
+            classify_compressibility(&random_data(1 << 16, 8)),
+            Compressibility::Incompressible
+        );
+        // ~7 bits per byte, too diverse for chance matches.
+        assert_eq!(
+            classify_compressibility(&random_data(4096, 7)),
+            Compressibility::Low
+        );
+        // ~4 bits per byte.
+        let text = b"Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do eiusmod \
+            tempor incididunt ut labore et dolore magna aliqua.";
+        assert_eq!(classify_compressibility(text), Compressibility::Medium);
+        assert_eq!(
+            classify_compressibility(&text.repeat(50)),
+            Compressibility::High
+        );
+    }
+
+    #[test]
+    fn scores_clear_cut_inputs() {
+        let random = compressibility_score(&random_data(1 << 16, 8));
+        assert!(random < 0.01, "{random}");
+
+        let same_byte = compressibility_score(&[42; 4096]);
+        assert!(same_byte > 0.99, "{same_byte}");
+
+        let text = b"Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do eiusmod \
+            tempor incididunt ut labore et dolore magna aliqua.";
+        let repetitive = compressibility_score(&text.repeat(50));
+        assert!(repetitive > HIGH_THRESHOLD, "{repetitive}");
+    }
+}
@@ -0,0 +1,140 @@
+//! A rough, greedy LZ parse of the input, built on the match estimator's hashing scheme.
+
+use crate::match_estimator::for_each_table_slot;
+
+/// How a byte is covered in the estimated LZ parse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Coverage {
+    /// Sent as a literal.
+    Literal,
+    /// The first byte of a match.
+    MatchStart,
+    /// Covered by the match that started at an earlier byte.
+    MatchContinue,
+}
+
+/// Estimates the number of tokens an LZ parser would produce for the input; where each match
+/// is one token, and each run of consecutive literals is one token.
+///
+/// Useful for estimating the size of the token stream (i.e. sequence headers) of an LZ
+/// compressor; which, unlike the literals, the entropy of the data says nothing about.
+///
+/// # Heuristic
+///
+/// The input is parsed greedily with the same hashing scheme as
+/// [`estimate_num_lz_matches_fast`]. A hash hit at a position not covered by a match starts a
+/// new match covering the next 3 bytes; further hits while inside a match extend it by
+/// another 3 bytes from the hit, rather than starting a new one. Every other byte is a literal.
+/// Real parsers pick matches differently (and have a minimum match length), so use this to
+/// compare transforms against each other, not to predict exact token counts.
+///
+/// # Returns
+///
+/// The estimated number of tokens; 0 for an empty input.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::estimate::estimate_lz_token_count;
+///
+/// // No 3 byte sequence repeats; so it's all one literal run.
+/// let data: Vec<u8> = (0..=255).collect();
+/// assert_eq!(estimate_lz_token_count(&data), 1);
+/// ```
+///
+/// [`estimate_num_lz_matches_fast`]: crate::match_estimator::estimate_num_lz_matches_fast
+pub fn estimate_lz_token_count(bytes: &[u8]) -> usize {
+    let mut tokens = 0;
+    let mut previous = Coverage::MatchContinue;
+    parse_lz_coverage(bytes, |_, coverage| {
+        tokens += match coverage {
+            Coverage::MatchStart => 1,
+            Coverage::Literal => (previous != Coverage::Literal) as usize,
+            Coverage::MatchContinue => 0,
+        };
+        previous = coverage;
+    });
+
+    tokens
+}
+
+/// Parses `bytes` greedily, calling `on_byte` with the position and [`Coverage`] of every byte,
+/// in order.
+///
+/// Each position is hashed in turn; a hit at a position outside of a match starts one covering
+/// the next 3 bytes, and a hit inside a match extends it.
+pub(super) fn parse_lz_coverage(bytes: &[u8], mut on_byte: impl FnMut(usize, Coverage)) {
+    // Positions before this index are covered by a previous match.
+    let mut covered_until = 0;
+    for_each_table_slot(bytes, |position, data, slot: &mut u32| {
+        let is_hit = *slot == data;
+        *slot = data;
+
+        let coverage = if position < covered_until {
+            Coverage::MatchContinue
+        } else if is_hit {
+            Coverage::MatchStart
+        } else {
+            Coverage::Literal
+        };
+
+        if is_hit {
+            covered_until = position + 3;
+        }
+        on_byte(position, coverage);
+    });
+
+    let hashed_positions = bytes.len().saturating_sub(3);
+    for position in hashed_positions..bytes.len() {
+        let coverage = if position < covered_until {
+            Coverage::MatchContinue
+        } else {
+            Coverage::Literal
+        };
+        on_byte(position, coverage);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::random_bytes;
+    use std::vec::Vec;
+
+    #[test]
+    fn literal_data_is_about_one_token() {
+        let ramp: Vec<u8> = (0..=255).collect();
+        assert_eq!(estimate_lz_token_count(&ramp), 1);
+
+        // A few chance 3 byte repeats at most.
+        let tokens = estimate_lz_token_count(&random_bytes(4096, 12345));
+        assert!(tokens <= 5, "got {tokens}");
+    }
+
+    #[test]
+    fn alternating_matches_and_literals_give_many_tokens() {
+        // A fixed 16 byte chunk, alternating with fresh random 16 byte chunks: after the first
+        // period, each fixed chunk is a match and each random chunk a literal run.
+        let random = random_bytes(16 * 201, 12345);
+        let (fixed, fresh) = random.split_at(16);
+        let mut data = Vec::new();
+        for chunk in fresh.chunks_exact(16) {
+            data.extend_from_slice(fixed);
+            data.extend_from_slice(chunk);
+        }
+
+        let tokens = estimate_lz_token_count(&data);
+        assert!((390..=410).contains(&tokens), "got {tokens}");
+    }
+
+    #[test]
+    fn coverage_visits_every_byte_in_order() {
+        let data = [7u8; 100];
+        let mut positions = Vec::new();
+        parse_lz_coverage(&data, |position, _| positions.push(position));
+        assert!(positions.iter().copied().eq(0..100));
+
+        assert_eq!(estimate_lz_token_count(&[]), 0);
+        assert_eq!(estimate_lz_token_count(&[1, 2]), 1);
+    }
+}
@@ -0,0 +1,723 @@
+//! Compressibility estimates built by combining the histogram, entropy and match estimator
+//! primitives.
+//!
+//! The primitives each measure one property of the data in isolation. The functions in this
+//! module combine them into rough models of what a real compressor would do, which are more
+//! convenient when the goal is picking between transforms rather than measuring the data.
+
+use crate::entropy::{analyze_entropy, shannon_entropy_of_histogram32};
+use crate::histogram::Histogram32;
+use crate::match_estimator::estimate_num_lz_matches_fast_with_histogram;
+use crate::math::{ceil, floor, round};
+use lz_parse::{parse_lz_coverage, Coverage};
+
+mod bits;
+pub use bits::*;
+mod classify;
+pub use classify::*;
+mod lz_parse;
+pub use lz_parse::*;
+mod period;
+pub use period::*;
+
+/// Estimates the order-0 entropy of the literal bytes left over after LZ matching.
+///
+/// An LZ compressor only sends the bytes it can't cover with a match as literals, and it's
+/// those literals that the entropy coder stage pays for. This runs the same hashing scheme as
+/// [`estimate_num_lz_matches_fast`] over each position, treats the 3 bytes at every position
+/// with a hash hit as covered by a match, and histograms only the remaining bytes.
+///
+/// # Arguments
+///
+/// * `bytes` - The input data stream.
+///
+/// # Returns
+///
+/// The Shannon entropy of the literal bytes, in bits per literal.
+/// Returns 0.0 if there are no literals.
+///
+/// # Remarks
+///
+/// This is a rough model. The match estimator is approximate (hash collisions count as
+/// matches, long range matches are missed), and real compressors pick matches differently;
+/// use this to compare transforms against each other, not to predict exact sizes.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::estimate::estimate_literal_entropy;
+///
+/// let data = [7u8; 4096];
+/// assert!(estimate_literal_entropy(&data) < 0.01);
+/// ```
+///
+/// [`estimate_num_lz_matches_fast`]: crate::match_estimator::estimate_num_lz_matches_fast
+pub fn estimate_literal_entropy(bytes: &[u8]) -> f64 {
+    let histogram = literal_histogram(bytes);
+    let total: u64 = histogram.counter.iter().map(|&x| x as u64).sum();
+    shannon_entropy_of_histogram32(&histogram.counter, total)
+}
+
+/// Estimates how well data would compress with run-length encoding (RLE).
+///
+/// Returns the fraction of bytes which belong to runs of 2 or more identical bytes; i.e. the
+/// share of the input an RLE scheme could collapse. See [`estimate_rle_savings_with_min_run`]
+/// for formats where short runs aren't worth encoding.
+///
+/// # Returns
+///
+/// A value in `0.0..=1.0`; 0.0 for an empty input.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::estimate::estimate_rle_savings;
+///
+/// assert_eq!(estimate_rle_savings(&[1, 1, 2, 3]), 0.5);
+/// ```
+pub fn estimate_rle_savings(bytes: &[u8]) -> f64 {
+    estimate_rle_savings_with_min_run(bytes, 2)
+}
+
+/// Estimates how well data would compress with run-length encoding (RLE), only counting runs
+/// of at least `min_run` identical bytes.
+///
+/// # Arguments
+///
+/// * `bytes` - The input data stream.
+/// * `min_run` - Minimum length of a run for its bytes to be counted. Values below 2 count
+///   every byte.
+///
+/// # Returns
+///
+/// The fraction of bytes which belong to runs of at least `min_run` bytes, in `0.0..=1.0`;
+/// 0.0 for an empty input.
+pub fn estimate_rle_savings_with_min_run(bytes: &[u8], min_run: usize) -> f64 {
+    if bytes.is_empty() {
+        return 0.0;
+    }
+
+    let mut in_runs = 0;
+    let mut run_start = 0;
+    for position in 1..=bytes.len() {
+        if position == bytes.len() || bytes[position] != bytes[run_start] {
+            let run_length = position - run_start;
+            if run_length >= min_run {
+                in_runs += run_length;
+            }
+            run_start = position;
+        }
+    }
+
+    in_runs as f64 / bytes.len() as f64
+}
+
+/// How to round a fractional number of bytes to a whole one.
+///
+/// Used by [`estimated_compressed_size_bytes_rounded`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rounding {
+    /// Round down; an optimistic (best-case) estimate.
+    Floor,
+    /// Round up; a pessimistic (worst-case) estimate.
+    #[default]
+    Ceil,
+    /// Round to the nearest byte, with halves rounded away from zero.
+    Nearest,
+}
+
+/// Estimates the size in bytes of the data in a [Histogram32] after ideal order-0 entropy
+/// coding, rounded up to a whole byte.
+///
+/// This is [`estimated_compressed_size_bytes_rounded`] with [`Rounding::Ceil`].
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::estimate::estimated_compressed_size_bytes;
+/// use lossless_transform_utils::histogram::Histogram32;
+///
+/// // 4 symbols at 1 bit each = 4 bits; rounded up to 1 byte.
+/// let histogram = Histogram32::from_bytes(&[0, 0, 1, 1]);
+/// assert_eq!(estimated_compressed_size_bytes(&histogram), 1);
+/// ```
+pub fn estimated_compressed_size_bytes(histogram: &Histogram32) -> u64 {
+    estimated_compressed_size_bytes_rounded(histogram, Rounding::Ceil)
+}
+
+/// Estimates the size in bytes of the data in a [Histogram32] after ideal order-0 entropy
+/// coding, rounded to a whole byte with the given [`Rounding`].
+///
+/// # Arguments
+///
+/// * `histogram` - Histogram of the data to estimate.
+/// * `rounding` - How to round the fractional byte count; [`Rounding::Floor`] and
+///   [`Rounding::Ceil`] give best and worst case bounds respectively.
+///
+/// # Remarks
+///
+/// The estimate excludes the cost of storing the code table itself; real coders also never
+/// reach the ideal code length exactly.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::estimate::{estimated_compressed_size_bytes_rounded, Rounding};
+/// use lossless_transform_utils::histogram::Histogram32;
+///
+/// // 4 bits total.
+/// let histogram = Histogram32::from_bytes(&[0, 0, 1, 1]);
+/// assert_eq!(estimated_compressed_size_bytes_rounded(&histogram, Rounding::Floor), 0);
+/// assert_eq!(estimated_compressed_size_bytes_rounded(&histogram, Rounding::Ceil), 1);
+/// ```
+pub fn estimated_compressed_size_bytes_rounded(histogram: &Histogram32, rounding: Rounding) -> u64 {
+    let bytes = analyze_entropy(histogram).total_bits / 8.0;
+    let rounded = match rounding {
+        Rounding::Floor => floor(bytes),
+        Rounding::Ceil => ceil(bytes),
+        Rounding::Nearest => round(bytes),
+    };
+    rounded as u64
+}
+
+/// Default minimum fraction of printable ASCII and whitespace bytes for
+/// [`looks_like_text`] to consider data text.
+pub const DEFAULT_TEXT_THRESHOLD: f64 = 0.95;
+
+/// Guesses whether data is (ASCII) text rather than binary.
+///
+/// This is [`looks_like_text_with_threshold`] with [`DEFAULT_TEXT_THRESHOLD`].
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::estimate::looks_like_text;
+///
+/// assert!(looks_like_text(b"Hello, world!\n"));
+/// assert!(!looks_like_text(&[0x7F, 0x45, 0x4C, 0x46, 0x02, 0x01, 0x01, 0x00]));
+/// ```
+pub fn looks_like_text(bytes: &[u8]) -> bool {
+    looks_like_text_with_threshold(bytes, DEFAULT_TEXT_THRESHOLD)
+}
+
+/// Guesses whether data is (ASCII) text rather than binary, e.g. for routing blocks to
+/// text or binary specific transforms.
+///
+/// Data is considered text if it contains no NUL bytes (which practically never occur in text,
+/// but are common in binary data), and at least `threshold` of its bytes are printable ASCII
+/// (`0x20..=0x7E`) or common whitespace (tab, line feed, form feed, carriage return).
+///
+/// # Arguments
+///
+/// * `bytes` - The data to check.
+/// * `threshold` - Minimum fraction (`0.0..=1.0`) of bytes which must be printable or
+///   whitespace. Lower it to accept text with some non-ASCII (e.g. UTF-8) characters, since
+///   bytes `0x80..=0xFF` don't count as printable.
+///
+/// # Returns
+///
+/// `true` if the data looks like text; `false` for binary data or an empty input.
+pub fn looks_like_text_with_threshold(bytes: &[u8], threshold: f64) -> bool {
+    if bytes.is_empty() {
+        return false;
+    }
+
+    let histogram = Histogram32::from_bytes(bytes);
+    if histogram.counter[0] != 0 {
+        return false;
+    }
+
+    let whitespace = b"\t\n\x0C\r";
+    let text_bytes: u64 = histogram.counter[0x20..=0x7E]
+        .iter()
+        .chain(
+            whitespace
+                .iter()
+                .map(|&byte| &histogram.counter[byte as usize]),
+        )
+        .map(|&count| count as u64)
+        .sum();
+
+    text_bytes as f64 >= threshold * bytes.len() as f64
+}
+
+/// Bits to store the code length of one symbol in the table header.
+const HEADER_BITS_PER_LENGTH: f64 = 4.0;
+/// Bits to identify one present symbol, when listing the present symbols individually.
+const HEADER_BITS_PER_SYMBOL: f64 = 8.0;
+/// Bits to identify the present symbols with a bitmap over all 256 byte values.
+const HEADER_BITMAP_BITS: f64 = 256.0;
+
+/// Estimates the size in bits of the data in a [Histogram32] after Huffman coding, including
+/// the cost of transmitting the code table.
+///
+/// [`analyze_entropy`] ignores the code table; for large blocks that's negligible, but for
+/// small blocks the table can cost more than the entropy coding saves. This is a more realistic
+/// estimate for deciding whether a small block is worth compressing at all.
+///
+/// # Table Cost Model
+///
+/// - The data costs `Σ count * length` bits, with lengths from an (unlimited length) Huffman
+///   code built from the histogram.
+/// - Each present symbol costs 4 bits to store its code length.
+/// - Identifying which symbols are present costs 8 bits per present symbol (a list of byte
+///   values), or 256 bits (a bitmap) if that's cheaper.
+///
+/// A block of a single repeated byte costs no data bits, as it can be sent as a run.
+///
+/// Real formats (e.g. zstd's FSE compressed Huffman weights) encode tables more compactly for
+/// large alphabets; treat this as an upper bound on the header cost.
+///
+/// # Returns
+///
+/// The estimated size in bits; 0.0 for an empty histogram.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::estimate::estimated_size_with_header_bits;
+/// use lossless_transform_utils::histogram::Histogram32;
+///
+/// // 2 symbols, 1 bit each; plus (4 + 8) bits of header for each symbol.
+/// let histogram = Histogram32::from_bytes(&[0, 0, 1, 1]);
+/// assert_eq!(estimated_size_with_header_bits(&histogram), 28.0);
+/// ```
+pub fn estimated_size_with_header_bits(histogram: &Histogram32) -> f64 {
+    let lengths = huffman_code_lengths(histogram);
+    let mut data_bits = 0_u64;
+    let mut symbols = 0_u32;
+    for (&count, &length) in histogram.counter.iter().zip(lengths.iter()) {
+        data_bits += count as u64 * length as u64;
+        symbols += (count != 0) as u32;
+    }
+
+    if symbols == 0 {
+        return 0.0;
+    }
+
+    let symbols = symbols as f64;
+    let header_bits = symbols * HEADER_BITS_PER_LENGTH
+        + (symbols * HEADER_BITS_PER_SYMBOL).min(HEADER_BITMAP_BITS);
+    data_bits as f64 + header_bits
+}
+
+/// The common per-block metrics used to decide whether (and how) to transform a block.
+///
+/// Returned by [`analyze_block`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlockMetrics {
+    /// The number of bytes in the block.
+    pub total: u64,
+    /// The order-0 Shannon entropy of the block; i.e. average number of bits per byte.
+    pub order0_entropy_bits: f64,
+    /// The estimated number of >=3 byte LZ matches in the block.
+    pub estimated_matches: usize,
+}
+
+/// Computes the byte count, order-0 entropy and estimated LZ match count of a block.
+///
+/// # Remarks
+///
+/// This makes two passes over the data: one to build the histogram, and one for the match
+/// estimator (plus clearing its 128KiB hash table). The match pass is skipped if the block
+/// consists of a single repeated byte, and the match count is then exactly `len - 3`; see
+/// [`estimate_num_lz_matches_fast_with_histogram`].
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::estimate::analyze_block;
+///
+/// let metrics = analyze_block(&[7u8; 1024]);
+/// assert_eq!(metrics.total, 1024);
+/// assert_eq!(metrics.order0_entropy_bits, 0.0);
+/// assert_eq!(metrics.estimated_matches, 1021);
+/// ```
+pub fn analyze_block(bytes: &[u8]) -> BlockMetrics {
+    let histogram = Histogram32::from_bytes(bytes);
+    let entropy = analyze_entropy(&histogram);
+    BlockMetrics {
+        total: entropy.total_symbols,
+        order0_entropy_bits: entropy.bits_per_symbol,
+        estimated_matches: estimate_num_lz_matches_fast_with_histogram(bytes, &histogram),
+    }
+}
+
+/// How a block is best compressed, as suggested by [`recommend_strategy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recommendation {
+    /// Store the block as-is; no stage is expected to shrink it meaningfully.
+    Store,
+    /// Run-length encode the block; it's (nearly) all long runs of identical bytes.
+    Rle,
+    /// LZ compress the block, then entropy code the result.
+    LzThenEntropy,
+    /// Entropy code the block alone; it has few repeats, but a skewed byte distribution.
+    EntropyOnly,
+}
+
+/// Minimum length of a run of identical bytes counted towards [`Recommendation::Rle`].
+const RECOMMEND_RLE_MIN_RUN: usize = 4;
+/// Minimum fraction of bytes in runs for [`Recommendation::Rle`].
+const RECOMMEND_RLE_MIN_SAVINGS: f64 = 0.9;
+/// Minimum estimated LZ matches per byte for [`Recommendation::LzThenEntropy`].
+const RECOMMEND_LZ_MIN_MATCH_RATIO: f64 = 0.25;
+/// Maximum order-0 entropy (bits per byte) for [`Recommendation::EntropyOnly`].
+const RECOMMEND_ENTROPY_MAX_BITS: f64 = 7.0;
+
+/// Suggests how to compress a block, by combining the RLE, LZ match and entropy estimates.
+///
+/// # Decision Tree
+///
+/// The first matching rule wins:
+///
+/// 1. Empty input: [`Recommendation::Store`].
+/// 2. At least 90% of bytes are in runs of 4 or more identical bytes (see
+///    [`estimate_rle_savings_with_min_run`]): [`Recommendation::Rle`].
+/// 3. The estimated number of LZ matches (see [`analyze_block`]) is at least 0.25 per byte:
+///    [`Recommendation::LzThenEntropy`].
+/// 4. The order-0 entropy is at most 7 bits per byte: [`Recommendation::EntropyOnly`].
+/// 5. Otherwise: [`Recommendation::Store`].
+///
+/// # Remarks
+///
+/// The thresholds are deliberately conservative, so that a block only gets a stage when that
+/// stage is clearly worth it; borderline blocks are better decided by trying the candidates.
+/// Header costs aren't considered, so very small blocks (below ~64 bytes) may be recommended a
+/// stage which doesn't pay for itself; see [`estimated_size_with_header_bits`].
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::estimate::{recommend_strategy, Recommendation};
+///
+/// assert_eq!(recommend_strategy(&[0u8; 4096]), Recommendation::Rle);
+/// assert_eq!(recommend_strategy(&b"abcdefgh".repeat(512)), Recommendation::LzThenEntropy);
+/// ```
+pub fn recommend_strategy(bytes: &[u8]) -> Recommendation {
+    if bytes.is_empty() {
+        return Recommendation::Store;
+    }
+
+    if estimate_rle_savings_with_min_run(bytes, RECOMMEND_RLE_MIN_RUN) >= RECOMMEND_RLE_MIN_SAVINGS
+    {
+        return Recommendation::Rle;
+    }
+
+    let metrics = analyze_block(bytes);
+    let match_ratio = metrics.estimated_matches as f64 / bytes.len() as f64;
+    if match_ratio >= RECOMMEND_LZ_MIN_MATCH_RATIO {
+        Recommendation::LzThenEntropy
+    } else if metrics.order0_entropy_bits <= RECOMMEND_ENTROPY_MAX_BITS {
+        Recommendation::EntropyOnly
+    } else {
+        Recommendation::Store
+    }
+}
+
+/// Histograms the bytes of `bytes` which are not covered by an (estimated) LZ match.
+fn literal_histogram(bytes: &[u8]) -> Histogram32 {
+    let mut histogram = Histogram32::default();
+    parse_lz_coverage(bytes, |position, coverage| {
+        if coverage == Coverage::Literal {
+            histogram.counter[bytes[position] as usize] += 1;
+        }
+    });
+
+    histogram
+}
+
+/// Builds the code lengths of a Huffman code for the histogram; 0 for absent byte values.
+fn huffman_code_lengths(histogram: &Histogram32) -> [u8; 256] {
+    let mut lengths = [0_u8; 256];
+    let mut leaves = [0_u8; 256];
+    let mut num_leaves = 0;
+    for (byte, &count) in histogram.counter.iter().enumerate() {
+        if count != 0 {
+            leaves[num_leaves] = byte as u8;
+            num_leaves += 1;
+        }
+    }
+
+    // A single symbol needs no bits; the block can be sent as a run.
+    if num_leaves < 2 {
+        return lengths;
+    }
+
+    let leaves = &mut leaves[..num_leaves];
+    leaves.sort_unstable_by_key(|&byte| histogram.counter[byte as usize]);
+
+    // Two queue construction: nodes [0, num_leaves) are the leaves by ascending count, and
+    // internal nodes are appended after them; which are created in ascending weight order too.
+    let mut weights = [0_u64; 511];
+    let mut parents = [0_u16; 511];
+    for (node, &byte) in leaves.iter().enumerate() {
+        weights[node] = histogram.counter[byte as usize] as u64;
+    }
+
+    let num_nodes = 2 * num_leaves - 1;
+    let mut next_leaf = 0;
+    let mut next_internal = num_leaves;
+    for node in num_leaves..num_nodes {
+        for _ in 0..2 {
+            let take_leaf = next_leaf < num_leaves
+                && (next_internal == node || weights[next_leaf] <= weights[next_internal]);
+            let child = if take_leaf {
+                next_leaf += 1;
+                next_leaf - 1
+            } else {
+                next_internal += 1;
+                next_internal - 1
+            };
+
+            weights[node] += weights[child];
+            parents[child] = node as u16;
+        }
+    }
+
+    // Parents always come after their children, so walking backwards from the root visits
+    // each parent before its children.
+    let mut depths = [0_u8; 511];
+    for node in (0..num_nodes - 1).rev() {
+        depths[node] = depths[parents[node] as usize] + 1;
+    }
+
+    for (node, &byte) in leaves.iter().enumerate() {
+        lengths[byte as usize] = depths[node];
+    }
+
+    lengths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::random_bytes;
+    use std::vec::Vec;
+
+    #[test]
+    fn repetitive_data_has_near_zero_literal_entropy() {
+        let data = [0xAAu8; 1 << 16];
+        let literals: u64 = literal_histogram(&data)
+            .counter
+            .iter()
+            .map(|&x| x as u64)
+            .sum();
+
+        assert!(literals <= 3, "got {literals} literals");
+        assert!(estimate_literal_entropy(&data) < 0.01);
+    }
+
+    #[test]
+    fn random_data_has_about_8_bits_of_literal_entropy() {
+        let entropy = estimate_literal_entropy(&random_bytes(1 << 17, 12345));
+        assert!(entropy > 7.9, "got {entropy}");
+    }
+
+    #[test]
+    fn rle_savings_of_single_run_is_one() {
+        assert_eq!(estimate_rle_savings(&[b'a'; 4096]), 1.0);
+    }
+
+    #[test]
+    fn rle_savings_of_non_repeating_data_is_zero() {
+        let data: Vec<u8> = (0..4096).map(|x| (x % 256) as u8).collect();
+        assert_eq!(estimate_rle_savings(&data), 0.0);
+        assert_eq!(estimate_rle_savings(&[]), 0.0);
+        assert!(estimate_rle_savings(&random_bytes(1 << 16, 12345)) < 0.01);
+    }
+
+    #[test]
+    fn rle_savings_respects_min_run() {
+        // Runs of 2, 3 and 5; 10 bytes total.
+        let data = [1, 1, 2, 2, 2, 3, 3, 3, 3, 3];
+        assert_eq!(estimate_rle_savings_with_min_run(&data, 2), 1.0);
+        assert_eq!(estimate_rle_savings_with_min_run(&data, 3), 0.8);
+        assert_eq!(estimate_rle_savings_with_min_run(&data, 4), 0.5);
+        assert_eq!(estimate_rle_savings_with_min_run(&data, 6), 0.0);
+    }
+
+    #[test]
+    fn compressed_size_roundings_differ_for_fractional_bytes() {
+        // 3 equally likely symbols over 6 bytes; log2(3) * 6 bits = ~9.51 bits = ~1.19 bytes.
+        let histogram = Histogram32::from_bytes(&[0, 0, 1, 1, 2, 2]);
+        assert_eq!(
+            estimated_compressed_size_bytes_rounded(&histogram, Rounding::Floor),
+            1
+        );
+        assert_eq!(
+            estimated_compressed_size_bytes_rounded(&histogram, Rounding::Nearest),
+            1
+        );
+        assert_eq!(
+            estimated_compressed_size_bytes_rounded(&histogram, Rounding::Ceil),
+            2
+        );
+        assert_eq!(estimated_compressed_size_bytes(&histogram), 2);
+
+        // 7 of one symbol, 1 of another; ~4.35 bits = ~0.54 bytes, which rounds up to nearest.
+        let mut data = [0u8; 8];
+        data[7] = 1;
+        let histogram = Histogram32::from_bytes(&data);
+        assert_eq!(
+            estimated_compressed_size_bytes_rounded(&histogram, Rounding::Floor),
+            0
+        );
+        assert_eq!(
+            estimated_compressed_size_bytes_rounded(&histogram, Rounding::Nearest),
+            1
+        );
+    }
+
+    #[test]
+    fn compressed_size_of_empty_histogram_is_zero() {
+        let histogram = Histogram32::default();
+        for rounding in [Rounding::Floor, Rounding::Ceil, Rounding::Nearest] {
+            assert_eq!(
+                estimated_compressed_size_bytes_rounded(&histogram, rounding),
+                0
+            );
+        }
+    }
+
+    #[test]
+    fn block_metrics_match_individual_functions() {
+        let data = random_bytes(1 << 16, 12345);
+        let metrics = analyze_block(&data);
+
+        assert_eq!(metrics.total, data.len() as u64);
+        assert_eq!(
+            metrics.order0_entropy_bits,
+            crate::entropy::entropy_of_bytes(&data)
+        );
+        assert_eq!(
+            metrics.estimated_matches,
+            crate::match_estimator::estimate_num_lz_matches_fast(&data)
+        );
+    }
+
+    #[test]
+    fn ascii_text_looks_like_text() {
+        let text = b"The quick brown fox jumps over the lazy dog.\r\n\tSecond line; 1234567890!\n";
+        assert!(looks_like_text(text));
+        assert!(looks_like_text(&text.repeat(100)));
+    }
+
+    #[test]
+    fn random_bytes_do_not_look_like_text() {
+        assert!(!looks_like_text(&random_bytes(4096, 12345)));
+        assert!(!looks_like_text(&[]));
+    }
+
+    #[test]
+    fn text_threshold_is_configurable() {
+        // 10% non-ASCII bytes, as in UTF-8 text with some accented characters.
+        let mut text = b"abcdefghi".repeat(10);
+        text.extend_from_slice(&[0xC3; 10]);
+
+        assert!(!looks_like_text(&text));
+        assert!(looks_like_text_with_threshold(&text, 0.85));
+
+        // A single NUL byte rules out text regardless of threshold.
+        text.push(0);
+        assert!(!looks_like_text_with_threshold(&text, 0.0));
+    }
+
+    #[test]
+    fn header_cost_makes_tiny_block_not_worth_compressing() {
+        // 16 distinct bytes; ideal coding halves the size, but not once the table is paid for.
+        let data: Vec<u8> = (0..16).collect();
+        let histogram = Histogram32::from_bytes(&data);
+        let raw_bits = data.len() as f64 * 8.0;
+
+        assert!(analyze_entropy(&histogram).total_bits < raw_bits);
+        assert!(estimated_size_with_header_bits(&histogram) > raw_bits);
+    }
+
+    #[test]
+    fn header_cost_is_negligible_for_large_block() {
+        let data = random_bytes(1 << 16, 12345);
+        let histogram = Histogram32::from_bytes(&data);
+        let entropy_bits = analyze_entropy(&histogram).total_bits;
+        let estimate = estimated_size_with_header_bits(&histogram);
+
+        assert!(estimate >= entropy_bits);
+        assert!(
+            estimate < entropy_bits * 1.01,
+            "{estimate} vs {entropy_bits}"
+        );
+    }
+
+    #[test]
+    fn huffman_lengths_of_dyadic_distribution_match_entropy() {
+        // Probabilities 1/2, 1/4, ..., 1/256, 1/256; Huffman is optimal here.
+        let mut histogram = Histogram32::default();
+        for byte in 0..8 {
+            histogram.counter[byte] = 128 >> byte;
+        }
+        histogram.counter[8] = 1;
+
+        let lengths = huffman_code_lengths(&histogram);
+        assert_eq!(&lengths[..10], &[1, 2, 3, 4, 5, 6, 7, 8, 8, 0]);
+
+        // 510 data bits, 9 * (4 + 8) header bits.
+        assert_eq!(estimated_size_with_header_bits(&histogram), 618.0);
+        assert!((analyze_entropy(&histogram).total_bits - 510.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn header_cost_of_single_symbol_and_empty() {
+        let histogram = Histogram32::from_bytes(&[9; 100]);
+        assert_eq!(estimated_size_with_header_bits(&histogram), 12.0);
+        assert_eq!(
+            estimated_size_with_header_bits(&Histogram32::default()),
+            0.0
+        );
+    }
+
+    #[test]
+    fn handles_tiny_inputs() {
+        for len in 0..8 {
+            let data: Vec<u8> = (0..len).collect();
+            let literals: u64 = literal_histogram(&data)
+                .counter
+                .iter()
+                .map(|&x| x as u64)
+                .sum();
+            assert_eq!(literals, len as u64);
+        }
+    }
+
+    #[test]
+    fn recommends_rle_for_runs() {
+        assert_eq!(recommend_strategy(&[7u8; 4096]), Recommendation::Rle);
+
+        let mut runs = Vec::new();
+        for byte in random_bytes(64, 12345) {
+            runs.extend_from_slice(&[byte; 64]);
+        }
+        assert_eq!(recommend_strategy(&runs), Recommendation::Rle);
+    }
+
+    #[test]
+    fn recommends_store_for_random_and_empty() {
+        assert_eq!(
+            recommend_strategy(&random_bytes(1 << 16, 12345)),
+            Recommendation::Store
+        );
+        assert_eq!(recommend_strategy(&[]), Recommendation::Store);
+    }
+
+    #[test]
+    fn recommends_lz_for_repetitive_text() {
+        let text = b"The quick brown fox jumps over the lazy dog. ".repeat(100);
+        assert_eq!(recommend_strategy(&text), Recommendation::LzThenEntropy);
+    }
+
+    #[test]
+    fn recommends_entropy_only_for_skewed_non_repeating_data() {
+        // 64 distinct values (6 bits) at random; too many 3 byte sequences for chance repeats.
+        let data: Vec<u8> = random_bytes(4096, 12345).iter().map(|x| x & 63).collect();
+        assert_eq!(recommend_strategy(&data), Recommendation::EntropyOnly);
+    }
+}
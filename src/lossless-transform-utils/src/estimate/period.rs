@@ -0,0 +1,139 @@
+//! Detection of fixed size records, from the distances at which 3 byte sequences repeat.
+
+use crate::match_estimator::for_each_table_slot;
+use core::alloc::Layout;
+use safe_allocator_api::RawAlloc;
+
+/// Estimates the record size (stride) of structured data, e.g. to pick the column count of a
+/// transform which splits records into columns.
+///
+/// # Heuristic
+///
+/// Every position is hashed with the same scheme as [`estimate_num_lz_matches_fast`]; the table
+/// remembers the last position each hash was seen at. When a 3 byte sequence repeats (verified
+/// against the data), the distance back to its previous occurrence is counted. In tabular data,
+/// fields which change slowly repeat exactly one record apart; so the most common distance is
+/// likely the record size.
+///
+/// This is a heuristic. Hash collisions overwrite table entries, hiding some repeats; and data
+/// with short runs (e.g. zero padding) also repeats at a distance of 1, which can outvote the
+/// records if they are mostly padding.
+///
+/// # Arguments
+///
+/// * `bytes` - The data to scan.
+/// * `max_period` - Largest distance to consider; repeats further apart are ignored. Values
+///   above `bytes.len() - 1` are treated as `bytes.len() - 1`.
+///
+/// # Returns
+///
+/// The most common distance in `1..=max_period`, ties going to the smaller one. `None` if fewer
+/// than 2 repeats were found, or the most common distance accounts for less than a quarter of
+/// them; i.e. no period is dominant.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::estimate::estimate_dominant_period;
+///
+/// // 6 byte records: an incrementing id, and a fixed tag.
+/// let records: Vec<u8> = (0..100_u8).flat_map(|id| [id, 0, b'T', b'A', b'G', b'!']).collect();
+/// assert_eq!(estimate_dominant_period(&records, 64), Some(6));
+/// assert_eq!(estimate_dominant_period(b"no repeats", 64), None);
+/// ```
+///
+/// [`estimate_num_lz_matches_fast`]: crate::match_estimator::estimate_num_lz_matches_fast
+pub fn estimate_dominant_period(bytes: &[u8], max_period: usize) -> Option<usize> {
+    if max_period == 0 || bytes.len() < 4 {
+        return None;
+    }
+
+    // No repeat can be further apart than the input is long.
+    let max_period = max_period.min(bytes.len() - 1);
+
+    // `counts[distance]` is the number of repeats found `distance` bytes back; index 0 is unused.
+    let counts_layout = Layout::array::<u32>(max_period + 1).unwrap();
+    let mut counts_alloc = RawAlloc::new_zeroed(counts_layout).unwrap();
+    let counts = unsafe {
+        core::slice::from_raw_parts_mut(counts_alloc.as_mut_ptr() as *mut u32, max_period + 1)
+    };
+
+    let mut repeats = 0_u32;
+    for_each_table_slot(bytes, |position, data, slot: &mut u32| {
+        // Entries store `position + 1`, so 0 means empty. Only the low 32 bits are kept; the
+        // bytes are compared below, so a wrapped (wrong) position is never counted.
+        let entry = *slot;
+        *slot = (position as u32).wrapping_add(1);
+        if entry == 0 {
+            return;
+        }
+
+        let distance = (position as u32).wrapping_sub(entry - 1) as usize;
+        if distance == 0 || distance > max_period || distance > position {
+            return;
+        }
+
+        let source = position - distance;
+        if bytes[source..source + 3] == data.to_le_bytes()[..3] {
+            counts[distance] += 1;
+            repeats += 1;
+        }
+    });
+
+    let (period, &count) = counts
+        .iter()
+        .enumerate()
+        .skip(1)
+        .rev()
+        .max_by_key(|&(_, &count)| count)?;
+
+    (count >= 2 && count as u64 * 4 >= repeats as u64).then_some(period)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::random_bytes;
+    use std::vec::Vec;
+
+    #[test]
+    fn finds_sixteen_byte_records() {
+        // 16 byte records: 4 random bytes (e.g. a float), a slowly incrementing u32 counter and
+        // an 8 byte constant name field.
+        let random = random_bytes(4 * 4096, 1);
+        let mut data = Vec::new();
+        for (record, value) in (0..4096_u32).zip(random.chunks_exact(4)) {
+            data.extend_from_slice(value);
+            data.extend_from_slice(&(record / 64).to_le_bytes());
+            data.extend_from_slice(b"SENSOR01");
+        }
+
+        assert_eq!(estimate_dominant_period(&data, 256), Some(16));
+    }
+
+    #[test]
+    fn random_data_has_no_dominant_period() {
+        let data = random_bytes(1 << 16, 1);
+        assert_eq!(estimate_dominant_period(&data, 256), None);
+    }
+
+    #[test]
+    fn periods_above_max_period_are_ignored() {
+        let mut data = random_bytes(100, 7);
+        data.extend_from_within(..);
+        data.extend_from_within(..100);
+
+        assert_eq!(estimate_dominant_period(&data, 100), Some(100));
+        assert_eq!(estimate_dominant_period(&data, 99), None);
+        assert_eq!(estimate_dominant_period(&data, 0), None);
+    }
+
+    #[test]
+    fn huge_max_period_is_clamped_to_input() {
+        let mut data = random_bytes(100, 7);
+        data.extend_from_within(..);
+
+        assert_eq!(estimate_dominant_period(&data, usize::MAX), Some(100));
+        assert_eq!(estimate_dominant_period(b"abcd", usize::MAX), None);
+    }
+}
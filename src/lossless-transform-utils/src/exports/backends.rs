@@ -0,0 +1,117 @@
+//! C exports reporting which implementations the crate uses on the current machine.
+
+use crate::backends::active_backends;
+use crate::histogram::DispatchKind;
+use crate::match_estimator::MatchBackend;
+
+/// Implementation used by the match estimator, returned by [`match_estimator_backend`].
+///
+/// C compatible version of [`MatchBackend`]. The values are fixed, so they can be logged and
+/// compared across library versions; some are reserved for backends which don't exist yet.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LtuMatchBackend {
+    /// Portable scalar implementation; any CPU.
+    Generic = 0,
+    /// Reserved for a hand written x86 assembly implementation; not currently returned.
+    X86Asm = 1,
+    /// AVX2 implementation; enabled by the `estimator-avx2` feature.
+    Avx2 = 2,
+    /// AVX512 implementation; enabled by the `estimator-avx512` feature.
+    Avx512 = 3,
+    /// Reserved for an ARM NEON implementation; not currently returned.
+    Neon = 4,
+}
+
+impl From<MatchBackend> for LtuMatchBackend {
+    fn from(backend: MatchBackend) -> Self {
+        match backend {
+            MatchBackend::Generic => Self::Generic,
+            MatchBackend::Avx2 => Self::Avx2,
+            MatchBackend::Avx512 => Self::Avx512,
+        }
+    }
+}
+
+/// Implementation used by the histogram, returned by [`histogram_backend`].
+///
+/// C compatible version of [`DispatchKind`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LtuHistogramBackend {
+    /// Portable batched implementation; any CPU.
+    Portable = 0,
+    /// Batched implementation using BMI1 instructions.
+    Bmi1 = 1,
+}
+
+impl From<DispatchKind> for LtuHistogramBackend {
+    fn from(kind: DispatchKind) -> Self {
+        match kind {
+            DispatchKind::Portable => Self::Portable,
+            DispatchKind::Bmi1 => Self::Bmi1,
+        }
+    }
+}
+
+/// Returns the implementation [`estimate_num_lz_matches_fast`] uses on the current machine.
+///
+/// Cheap to call; useful for logging, e.g. when comparing performance across machines. See
+/// [`crate::backends::active_backends`].
+///
+/// # Example
+///
+/// ```c
+/// // C code example
+/// printf("match estimator backend: %d\n", (int)match_estimator_backend());
+/// ```
+///
+/// [`estimate_num_lz_matches_fast`]: super::estimate_num_lz_matches_fast
+#[no_mangle]
+pub extern "C" fn match_estimator_backend() -> LtuMatchBackend {
+    active_backends().match_estimator.into()
+}
+
+/// Returns the implementation [`histogram32_from_bytes`] uses on the current machine, for
+/// inputs large enough to use the batched implementation.
+///
+/// Cheap to call; useful for logging, e.g. when comparing performance across machines. See
+/// [`crate::backends::active_backends`].
+///
+/// # Example
+///
+/// ```c
+/// // C code example
+/// if (histogram_backend() == Portable) {
+///     // no BMI1 on this machine
+/// }
+/// ```
+///
+/// [`histogram32_from_bytes`]: super::histogram32_from_bytes
+#[no_mangle]
+pub extern "C" fn histogram_backend() -> LtuHistogramBackend {
+    active_backends().histogram.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exported_backends_match_rust_backends() {
+        let backends = active_backends();
+
+        let expected = match backends.match_estimator {
+            MatchBackend::Generic => 0,
+            MatchBackend::Avx2 => 2,
+            MatchBackend::Avx512 => 3,
+        };
+        assert_eq!(match_estimator_backend() as u32, expected);
+
+        let expected = match backends.histogram {
+            DispatchKind::Portable => 0,
+            DispatchKind::Bmi1 => 1,
+        };
+        assert_eq!(histogram_backend() as u32, expected);
+    }
+}
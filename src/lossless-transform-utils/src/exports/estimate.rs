@@ -0,0 +1,297 @@
+//! C exports of the compressibility estimates, and other one-call block scoring.
+
+use crate::estimate::{BlockMetrics, Compressibility};
+use core::slice;
+
+/// Per-block metrics returned by [`analyze_block`].
+///
+/// C compatible version of [`BlockMetrics`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlockMetricsC {
+    /// The number of bytes in the block.
+    pub total: u64,
+    /// The order-0 Shannon entropy of the block; i.e. average number of bits per byte.
+    pub order0_entropy_bits: f64,
+    /// The estimated number of >=3 byte LZ matches in the block.
+    pub estimated_matches: usize,
+}
+
+impl From<BlockMetrics> for BlockMetricsC {
+    fn from(metrics: BlockMetrics) -> Self {
+        Self {
+            total: metrics.total,
+            order0_entropy_bits: metrics.order0_entropy_bits,
+            estimated_matches: metrics.estimated_matches,
+        }
+    }
+}
+
+/// Computes the byte count, order-0 entropy and estimated LZ match count of a block in one call.
+///
+/// # Arguments
+///
+/// * `data` - Pointer to the input data stream to analyze
+/// * `len` - Length of the input data stream in bytes
+///
+/// # Returns
+///
+/// A [`BlockMetricsC`] with the results. The total and entropy are the same as calling
+/// [`histogram32_from_bytes`] and [`shannon_entropy_of_histogram32`] individually. The match
+/// count is that of [`estimate_num_lz_matches_fast`], except for a block of a single repeated
+/// byte, where it is exactly `len - 3` (see Notes).
+///
+/// [`histogram32_from_bytes`]: super::histogram32_from_bytes
+/// [`shannon_entropy_of_histogram32`]: super::shannon_entropy_of_histogram32
+/// [`estimate_num_lz_matches_fast`]: super::estimate_num_lz_matches_fast
+///
+/// # Example
+///
+/// ```c
+/// // C code example
+/// uint8_t data[] = "hello world hello world hello";
+/// BlockMetricsC metrics = analyze_block(data, strlen((char*)data));
+/// // metrics.total == 29, metrics.order0_entropy_bits and metrics.estimated_matches filled in
+/// ```
+///
+/// # Notes
+///
+/// This makes two passes over the data; one to build the histogram and one for the match
+/// estimator, which also allocates and clears a 128KiB hash table. The match pass is skipped
+/// for blocks consisting of a single repeated byte; every position after the first 3 bytes is
+/// then counted as a match, giving `len - 3` matches.
+///
+/// # Safety
+///
+/// The caller must ensure `data` points to a valid region of memory of at least `len` bytes.
+/// This API does not validate input parameters, passing a null pointer or invalid length will
+/// result in undefined behavior.
+#[no_mangle]
+pub unsafe extern "C" fn analyze_block(data: *const u8, len: usize) -> BlockMetricsC {
+    crate::estimate::analyze_block(slice::from_raw_parts(data, len)).into()
+}
+
+/// Calculates the order-0 Shannon entropy of a byte array, i.e. the average number of bits per
+/// byte an ideal entropy coder would need.
+///
+/// Builds the histogram internally; so scoring a block takes a single call and a single pass
+/// over the data, without an intermediate [`Histogram32`] on the C side.
+///
+/// # Arguments
+///
+/// * `data` - Pointer to the input data stream to analyze
+/// * `len` - Length of the input data stream in bytes
+///
+/// # Returns
+///
+/// The entropy in bits per byte; identical to calling [`histogram32_from_bytes`] and then
+/// [`code_length_of_histogram32_no_size`]. 0.0 for an empty input.
+///
+/// [`Histogram32`]: crate::histogram::Histogram32
+/// [`histogram32_from_bytes`]: super::histogram32_from_bytes
+/// [`code_length_of_histogram32_no_size`]: super::code_length_of_histogram32_no_size
+///
+/// # Example
+///
+/// ```c
+/// // C code example
+/// uint8_t data[] = {1, 2, 3, 4};
+/// double bits_per_byte = entropy_of_bytes(data, sizeof(data)); // 2.0
+/// ```
+///
+/// # Safety
+///
+/// The caller must ensure `data` points to a valid region of memory of at least `len` bytes.
+/// This API does not validate input parameters, passing a null pointer or invalid length will
+/// result in undefined behavior.
+#[no_mangle]
+pub unsafe extern "C" fn entropy_of_bytes(data: *const u8, len: usize) -> f64 {
+    crate::entropy::entropy_of_bytes(slice::from_raw_parts(data, len))
+}
+
+/// How compressible a block is, returned by [`classify_compressibility`].
+///
+/// C compatible version of [`Compressibility`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LtuCompressibility {
+    /// Expected savings below 5%; not worth compressing.
+    Incompressible,
+    /// Expected savings of 5% to 25%.
+    Low,
+    /// Expected savings of 25% to 60%.
+    Medium,
+    /// Expected savings of 60% or more.
+    High,
+}
+
+impl From<Compressibility> for LtuCompressibility {
+    fn from(compressibility: Compressibility) -> Self {
+        match compressibility {
+            Compressibility::Incompressible => Self::Incompressible,
+            Compressibility::Low => Self::Low,
+            Compressibility::Medium => Self::Medium,
+            Compressibility::High => Self::High,
+        }
+    }
+}
+
+/// Classifies how compressible a block is into one of 4 coarse levels.
+///
+/// The block is scored by an equally weighted blend of its expected entropy coding savings
+/// (`1 - order0_entropy_bits / 8`) and its estimated LZ matches per byte; then a score below
+/// 0.05 is [`LtuCompressibility::Incompressible`], below 0.25 [`LtuCompressibility::Low`], below
+/// 0.6 [`LtuCompressibility::Medium`], and anything higher [`LtuCompressibility::High`]. See
+/// [`crate::estimate::classify_compressibility`] for details.
+///
+/// # Arguments
+///
+/// * `data` - Pointer to the input data stream to classify
+/// * `len` - Length of the input data stream in bytes
+///
+/// # Example
+///
+/// ```c
+/// // C code example
+/// uint8_t data[4096] = {0};
+/// if (classify_compressibility(data, sizeof(data)) == High) {
+///     // compress it
+/// }
+/// ```
+///
+/// # Safety
+///
+/// The caller must ensure `data` points to a valid region of memory of at least `len` bytes.
+/// This API does not validate input parameters, passing a null pointer or invalid length will
+/// result in undefined behavior.
+#[no_mangle]
+pub unsafe extern "C" fn classify_compressibility(
+    data: *const u8,
+    len: usize,
+) -> LtuCompressibility {
+    crate::estimate::classify_compressibility(slice::from_raw_parts(data, len)).into()
+}
+
+/// Scores how compressible a block is, as a single number from 0.0 to 1.0; higher is more
+/// compressible.
+///
+/// The score is an equally weighted blend of the block's expected entropy coding savings
+/// (`1 - order0_entropy_bits / 8`) and its estimated LZ matches per byte; it's what
+/// [`classify_compressibility`] maps to a level. See [`crate::estimate::compressibility_score`]
+/// for details.
+///
+/// # Arguments
+///
+/// * `data` - Pointer to the input data stream to score
+/// * `len` - Length of the input data stream in bytes
+///
+/// # Returns
+///
+/// The score; 0.0 for an empty block.
+///
+/// # Example
+///
+/// ```c
+/// // C code example
+/// for (size_t x = 0; x < num_blocks; x++) {
+///     scores[x] = compressibility_score(blocks[x], block_sizes[x]);
+/// }
+/// // sort blocks by score, compress the best ones first
+/// ```
+///
+/// # Safety
+///
+/// The caller must ensure `data` points to a valid region of memory of at least `len` bytes.
+/// This API does not validate input parameters, passing a null pointer or invalid length will
+/// result in undefined behavior.
+#[no_mangle]
+pub unsafe extern "C" fn compressibility_score(data: *const u8, len: usize) -> f64 {
+    crate::estimate::compressibility_score(slice::from_raw_parts(data, len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exports::{
+        code_length_of_histogram32_no_size, estimate_num_lz_matches_fast, histogram32_from_bytes,
+        shannon_entropy_of_histogram32,
+    };
+    use crate::histogram::Histogram32;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_analyze_block() {
+        let test_data = b"hello world hello world hello";
+        let mut histogram = Histogram32::default();
+        crate::histogram::histogram32_from_bytes(test_data, &mut histogram);
+        let total = test_data.len() as u64;
+
+        let metrics = unsafe { analyze_block(test_data.as_ptr(), test_data.len()) };
+
+        assert_eq!(metrics.total, total);
+        assert_eq!(metrics.order0_entropy_bits, unsafe {
+            shannon_entropy_of_histogram32(&histogram, total)
+        });
+        assert_eq!(metrics.estimated_matches, unsafe {
+            estimate_num_lz_matches_fast(test_data.as_ptr(), test_data.len())
+        });
+    }
+
+    #[test]
+    fn test_analyze_block_single_repeated_byte() {
+        let test_data = [7u8; 1024];
+        let metrics = unsafe { analyze_block(test_data.as_ptr(), test_data.len()) };
+
+        assert_eq!(metrics.total, 1024);
+        assert_eq!(metrics.order0_entropy_bits, 0.0);
+        assert_eq!(metrics.estimated_matches, 1021);
+    }
+
+    #[test]
+    fn test_entropy_of_bytes() {
+        let text = b"hello world hello world hello";
+        let sequential: Vec<u8> = (0..4096).map(|x| x as u8).collect();
+
+        for data in [&text[..], &sequential[..], &[7; 100], &[]] {
+            let mut histogram = Histogram32::default();
+            let two_step = unsafe {
+                histogram32_from_bytes(data.as_ptr(), data.len(), &mut histogram);
+                code_length_of_histogram32_no_size(&histogram)
+            };
+
+            assert_eq!(
+                unsafe { entropy_of_bytes(data.as_ptr(), data.len()) },
+                two_step
+            );
+        }
+    }
+
+    #[test]
+    fn test_classify_compressibility() {
+        let random: Vec<u8> = (0..1_u32 << 16)
+            .map(|x| (x.wrapping_mul(0x9E3779B1) >> 24) as u8)
+            .collect();
+        let text = b"hello world hello world hello";
+        let zeros = [0u8; 4096];
+
+        for data in [&random[..], &text[..], &zeros[..], &[]] {
+            let c_class = unsafe { classify_compressibility(data.as_ptr(), data.len()) };
+            let rust_class = crate::estimate::classify_compressibility(data);
+            assert_eq!(c_class, rust_class.into());
+        }
+
+        let zeros_class = unsafe { classify_compressibility(zeros.as_ptr(), zeros.len()) };
+        assert_eq!(zeros_class, LtuCompressibility::High);
+    }
+
+    #[test]
+    fn test_compressibility_score() {
+        let text = b"hello world hello world hello";
+        let zeros = [0u8; 4096];
+
+        for data in [&text[..], &zeros[..], &[]] {
+            let c_score = unsafe { compressibility_score(data.as_ptr(), data.len()) };
+            assert_eq!(c_score, crate::estimate::compressibility_score(data));
+        }
+    }
+}
@@ -1,5 +1,10 @@
 use crate::{histogram::Histogram32, match_estimator};
-use core::slice;
+use core::{ptr, slice};
+
+mod backends;
+pub use backends::*;
+mod estimate;
+pub use estimate::*;
 
 /// Calculates a new histogram given a byte slice.
 ///
@@ -19,7 +24,8 @@ use core::slice;
 ///
 /// * `data` - Pointer to the first byte of the input data array
 /// * `length` - Number of bytes in the input data array
-/// * `hist` - Pointer to a [`Histogram32`] struct that will be populated with the results
+/// * `hist` - Pointer to a [`Histogram32`] struct that will be populated with the results.
+///   Counts are added onto those already in it, so it should be zero initialized.
 ///
 /// # Returns
 ///
@@ -58,6 +64,72 @@ pub unsafe extern "C" fn histogram32_from_bytes(
     crate::histogram::histogram32_from_bytes(slice::from_raw_parts(data, length), &mut *hist);
 }
 
+/// Safe Rust wrapper over the C export [`histogram32_from_bytes`], returning a new histogram.
+///
+/// For Rust code going through the same entry point as C callers; e.g. a crate which exposes
+/// the C ABI and wants an ergonomic Rust function next to it. Within Rust, this is identical to
+/// [`Histogram32::from_bytes`].
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::exports::histogram32_from_bytes_safe;
+///
+/// let histogram = histogram32_from_bytes_safe(&[1, 2, 3, 1]);
+/// assert_eq!(histogram.counter[1], 2);
+/// ```
+pub fn histogram32_from_bytes_safe(bytes: &[u8]) -> Histogram32 {
+    let mut hist = Histogram32::default();
+    unsafe { histogram32_from_bytes(bytes.as_ptr(), bytes.len(), &mut hist) };
+    hist
+}
+
+/// Adds the byte occurrences of a byte slice onto the counts already in a histogram.
+///
+/// An alias of [`histogram32_from_bytes`], which already keeps existing counts; this name
+/// makes the intent clear at call sites building one histogram from data that arrives in
+/// blocks (e.g. a whole file analyzed block by block). Call it once per block on the same
+/// histogram, zero initializing only before the first call. The result is the same as a single
+/// [`histogram32_from_bytes`] call over the concatenation of all blocks.
+///
+/// # Arguments
+///
+/// * `data` - Pointer to the first byte of the input data array
+/// * `length` - Number of bytes in the input data array
+/// * `hist` - Pointer to a [`Histogram32`] struct to add the counts to. Existing counts are kept.
+///
+/// # Example
+///
+/// ```c
+/// // C code example
+/// Histogram32 file_hist = {0}; // Initialize to zero once
+/// for (size_t x = 0; x < num_blocks; x++) {
+///     Histogram32 block_hist = {0};
+///     histogram32_from_bytes(blocks[x], block_sizes[x], &block_hist);
+///     double block_entropy = code_length_of_histogram32_no_size(&block_hist);
+///
+///     histogram32_accumulate_from_bytes(blocks[x], block_sizes[x], &file_hist);
+/// }
+/// ```
+///
+/// # Notes
+///
+/// Counts are 32-bit; each byte value must occur fewer than 2^32 times across all calls.
+///
+/// # Safety
+///
+/// This function assumes the provided pointers and length are valid:
+/// - `data` must point to a valid memory region of at least `length` bytes
+/// - `hist` must point to a valid, writable, initialized [`Histogram32`] struct
+#[no_mangle]
+pub unsafe extern "C" fn histogram32_accumulate_from_bytes(
+    data: *const u8,
+    length: usize,
+    hist: *mut Histogram32,
+) {
+    histogram32_from_bytes(data, length, hist)
+}
+
 /// Gets the count for a specific byte value from the histogram.
 ///
 /// # Arguments
@@ -287,6 +359,63 @@ pub unsafe extern "C" fn estimate_num_lz_matches_fast(data: *const u8, len: usiz
     match_estimator::estimate_num_lz_matches_fast(slice::from_raw_parts(data, len))
 }
 
+/// Estimates the number of >=3 byte LZ matches in a given input data stream, using a
+/// caller provided hash table instead of allocating one.
+///
+/// This lets callers without a (Rust) global allocator use the estimator; otherwise it is
+/// identical to [`estimate_num_lz_matches_fast`].
+///
+/// # Arguments
+///
+/// * `data` - Pointer to the input data stream to analyze
+/// * `len` - Length of the input data stream in bytes
+/// * `table` - Pointer to scratch space of `table_len` `uint32_t` elements, used as the hash table.
+///   Its contents do not need to be initialized; they are cleared before use.
+/// * `table_len` - Number of elements in `table`. Must be equal to [`MATCH_TABLE_LEN`] (32768,
+///   i.e. 128KiB).
+///
+/// # Returns
+///
+/// The estimated number of >=3 byte LZ matches that could be found in the data.
+/// Returns 0 if `table_len` is not [`MATCH_TABLE_LEN`] or `table` is null.
+///
+/// # Example
+///
+/// ```c
+/// // C code example
+/// static uint32_t table[32768];
+/// uint8_t data[] = "hello world hello world hello";
+/// size_t matches = estimate_num_lz_matches_fast_in(data, strlen((char*)data), table, 32768);
+/// ```
+///
+/// # Safety
+///
+/// The caller must ensure `data` points to a valid region of memory of at least `len` bytes,
+/// and that `table` points to a writable, 4 byte aligned region of `table_len` `uint32_t`
+/// elements that doesn't overlap `data`.
+///
+/// [`MATCH_TABLE_LEN`]: match_estimator::MATCH_TABLE_LEN
+#[no_mangle]
+pub unsafe extern "C" fn estimate_num_lz_matches_fast_in(
+    data: *const u8,
+    len: usize,
+    table: *mut u32,
+    table_len: usize,
+) -> usize {
+    if table.is_null() || table_len != match_estimator::MATCH_TABLE_LEN {
+        return 0;
+    }
+
+    // Zeroed through the raw pointer first, as a reference may only be made to initialized memory.
+    ptr::write_bytes(table, 0, match_estimator::MATCH_TABLE_LEN);
+    let table = &mut *(table as *mut [u32; match_estimator::MATCH_TABLE_LEN]);
+    match_estimator::estimate_num_lz_matches_fast_with_table(
+        slice::from_raw_parts(data, len),
+        table,
+        false,
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -314,6 +443,39 @@ mod tests {
         assert_eq!(c_histogram.counter[255], 1); // byte 255 appears once
     }
 
+    #[test]
+    fn test_histogram32_from_bytes_safe() {
+        let test_data = [1u8, 2, 3, 1, 2, 1, 0, 255];
+        let mut rust_histogram = Histogram32::default();
+        crate::histogram::histogram32_from_bytes(&test_data, &mut rust_histogram);
+
+        assert_eq!(
+            histogram32_from_bytes_safe(&test_data).counter,
+            rust_histogram.counter
+        );
+        assert_eq!(
+            histogram32_from_bytes_safe(&[]).counter,
+            Histogram32::default().counter
+        );
+    }
+
+    #[test]
+    fn test_histogram32_accumulate_from_bytes() {
+        let test_data: [u8; 300] = core::array::from_fn(|i| (i * 7 % 256) as u8);
+        let (first, second) = test_data.split_at(100);
+        let mut c_histogram = Histogram32::default();
+        let mut rust_histogram = Histogram32::default();
+
+        unsafe {
+            histogram32_accumulate_from_bytes(first.as_ptr(), first.len(), &mut c_histogram);
+            histogram32_accumulate_from_bytes(second.as_ptr(), second.len(), &mut c_histogram);
+        }
+        crate::histogram::histogram32_from_bytes(&test_data, &mut rust_histogram);
+
+        // Accumulating both halves should be identical to a single call over all of the data
+        assert_eq!(c_histogram.counter, rust_histogram.counter);
+    }
+
     #[test]
     fn test_histogram32_get_count() {
         let test_data = [1u8, 2, 3, 1, 2, 1];
@@ -429,6 +591,42 @@ mod tests {
         assert_eq!(rust_estimate, c_estimate);
     }
 
+    #[test]
+    fn test_estimate_num_lz_matches_fast_in() {
+        let test_data = b"hello world hello world hello";
+        let mut table = std::vec![0u32; match_estimator::MATCH_TABLE_LEN];
+
+        let c_estimate =
+            unsafe { estimate_num_lz_matches_fast(test_data.as_ptr(), test_data.len()) };
+        let c_estimate_in = unsafe {
+            estimate_num_lz_matches_fast_in(
+                test_data.as_ptr(),
+                test_data.len(),
+                table.as_mut_ptr(),
+                table.len(),
+            )
+        };
+
+        assert_eq!(c_estimate, c_estimate_in);
+    }
+
+    #[test]
+    fn test_estimate_num_lz_matches_fast_in_wrong_table_len() {
+        let test_data = [0u8; 64];
+        let mut table = std::vec![0u32; 16];
+
+        let c_estimate = unsafe {
+            estimate_num_lz_matches_fast_in(
+                test_data.as_ptr(),
+                test_data.len(),
+                table.as_mut_ptr(),
+                table.len(),
+            )
+        };
+
+        assert_eq!(c_estimate, 0);
+    }
+
     #[test]
     fn test_histogram_with_empty_data() {
         let test_data: &[u8] = &[];
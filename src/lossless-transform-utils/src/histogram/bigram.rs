@@ -0,0 +1,123 @@
+//! Order-1 (bigram) byte histograms; counts of each byte, split by the byte before it.
+
+use super::most_common_of;
+use std::boxed::Box;
+use std::vec;
+
+/// Counts of each pair of consecutive bytes; i.e. a [`Histogram32`] of the next byte for each
+/// possible previous byte.
+///
+/// Row `prev` holds the counts of the bytes which followed `prev`. The table is 256KiB, so it
+/// lives on the heap.
+///
+/// [`Histogram32`]: super::Histogram32
+#[derive(Clone, PartialEq, Eq)]
+pub struct Bigram32 {
+    counter: Box<[[u32; 256]; 256]>,
+}
+
+impl Default for Bigram32 {
+    fn default() -> Self {
+        Self {
+            counter: vec![[0u32; 256]; 256]
+                .into_boxed_slice()
+                .try_into()
+                .unwrap(),
+        }
+    }
+}
+
+impl Bigram32 {
+    /// Counts every pair of consecutive bytes in `bytes`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lossless_transform_utils::histogram::Bigram32;
+    ///
+    /// let bigram = Bigram32::from_bytes(b"abab");
+    /// assert_eq!(bigram.count(b'a', b'b'), 2);
+    /// assert_eq!(bigram.count(b'b', b'a'), 1);
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        let mut bigram = Self::default();
+        bigram.add_bytes(bytes);
+        bigram
+    }
+
+    /// Adds the pairs of consecutive bytes in `bytes` onto the existing counts.
+    ///
+    /// Pairs spanning two calls aren't counted; pass overlapping slices (sharing one byte) to
+    /// count a stream in chunks.
+    pub fn add_bytes(&mut self, bytes: &[u8]) {
+        for pair in bytes.windows(2) {
+            self.counter[pair[0] as usize][pair[1] as usize] += 1;
+        }
+    }
+
+    /// Returns how many times `next` followed `prev`.
+    pub fn count(&self, prev: u8, next: u8) -> u32 {
+        self.counter[prev as usize][next as usize]
+    }
+
+    /// Returns the counts of each byte which followed `prev`.
+    pub fn row(&self, prev: u8) -> &[u32; 256] {
+        &self.counter[prev as usize]
+    }
+}
+
+/// Returns the byte most likely to follow `prev`, and how many times it did.
+///
+/// A building block for order-1 predictors, e.g. a transform replacing each byte by whether it
+/// matches the prediction. Ties are broken in favour of the lowest byte value, as in every
+/// 'most common' query of this module.
+///
+/// # Returns
+///
+/// The `(next, count)` with the highest count in the row of `prev`; or [`None`] if `prev` was
+/// never followed by another byte.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::histogram::{most_likely_successor, Bigram32};
+///
+/// let bigram = Bigram32::from_bytes(b"the then them");
+/// assert_eq!(most_likely_successor(&bigram, b't'), Some((b'h', 3)));
+/// assert_eq!(most_likely_successor(&bigram, b'x'), None);
+/// ```
+pub fn most_likely_successor(bigram: &Bigram32, prev: u8) -> Option<(u8, u32)> {
+    let row = bigram.row(prev).iter().enumerate();
+    most_common_of(row.map(|(next, &count)| (next as u8, count)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn successor_follows_strong_pattern() {
+        // 'a' is followed by 'b' 90% of the time, and by 'c' otherwise.
+        let mut data = Vec::new();
+        for index in 0..1000 {
+            data.push(b'a');
+            data.push(if index % 10 == 0 { b'c' } else { b'b' });
+        }
+        let bigram = Bigram32::from_bytes(&data);
+
+        assert_eq!(most_likely_successor(&bigram, b'a'), Some((b'b', 900)));
+        assert_eq!(bigram.count(b'a', b'c'), 100);
+        // 'b' and 'c' are always followed by 'a'; except the final 'b'.
+        assert_eq!(most_likely_successor(&bigram, b'b'), Some((b'a', 899)));
+        assert_eq!(most_likely_successor(&bigram, b'z'), None);
+    }
+
+    #[test]
+    fn successor_ties_pick_lowest_byte() {
+        let bigram = Bigram32::from_bytes(&[5, 9, 5, 7]);
+        assert_eq!(most_likely_successor(&bigram, 5), Some((7, 1)));
+        // The last byte has no successor.
+        assert_eq!(most_likely_successor(&bigram, 7), None);
+    }
+}
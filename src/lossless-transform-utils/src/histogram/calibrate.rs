@@ -0,0 +1,121 @@
+//! Runtime calibration of the size threshold used by [`histogram32_from_bytes`].
+//!
+//! [`histogram32_from_bytes`]: super::histogram32_from_bytes
+
+use super::{histogram32_generic_batched_unroll_4_u32, histogram32_reference, Histogram32};
+use core::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Smallest and largest threshold [`calibrate_histogram_threshold`] can return.
+const MIN_THRESHOLD: usize = 16;
+const MAX_THRESHOLD: usize = 256;
+
+/// Input sizes are probed in steps of this many bytes.
+const STEP: usize = 16;
+
+/// Number of histograms computed per timing sample; keeps each sample well above timer resolution.
+const CALLS_PER_SAMPLE: usize = 256;
+
+/// Number of samples per size and implementation; the fastest one is kept.
+const SAMPLES: usize = 16;
+
+/// Measures the input size at which the batched histogram implementation starts outperforming
+/// the simple reference implementation on the current machine.
+///
+/// The default [`DEFAULT_BATCHED_THRESHOLD`] was tuned on a 5900X; the result of this function
+/// can be passed to [`histogram32_from_bytes_with_threshold`] instead, to tune for the machine
+/// the code actually runs on.
+///
+/// # Methodology
+///
+/// For each input size from 16 to 256 bytes (in 16 byte steps), both implementations histogram
+/// the same buffer many times in a row. Each measurement is repeated, keeping the fastest
+/// sample to filter out interrupts and frequency changes. The first size at which the batched
+/// implementation is at least as fast as the reference one is returned.
+///
+/// # Returns
+///
+/// The crossover size in bytes, in the range `16..=256`.
+///
+/// # Remarks
+///
+/// This takes a few milliseconds, so call it once at startup and keep the result;
+/// not on the hot path.
+///
+/// [`DEFAULT_BATCHED_THRESHOLD`]: super::DEFAULT_BATCHED_THRESHOLD
+/// [`histogram32_from_bytes_with_threshold`]: super::histogram32_from_bytes_with_threshold
+pub fn calibrate_histogram_threshold() -> usize {
+    // Mixed, non-repeating data, so neither implementation gets an unrealistically easy input.
+    let mut state: u32 = 12345;
+    let data: [u8; MAX_THRESHOLD] = core::array::from_fn(|_| {
+        state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+        (state >> 24) as u8
+    });
+
+    crossover(|size| {
+        let input = &data[..size];
+        let reference = fastest_sample(input, histogram32_reference);
+        let batched = fastest_sample(input, histogram32_generic_batched_unroll_4_u32);
+        (reference, batched)
+    })
+}
+
+/// Returns the first probed size at which `measure` reports the batched implementation (second
+/// element) as at least as fast as the reference one (first element); or [`MAX_THRESHOLD`].
+fn crossover(mut measure: impl FnMut(usize) -> (Duration, Duration)) -> usize {
+    for size in (MIN_THRESHOLD..=MAX_THRESHOLD).step_by(STEP) {
+        let (reference, batched) = measure(size);
+        if batched <= reference {
+            return size;
+        }
+    }
+
+    MAX_THRESHOLD
+}
+
+fn fastest_sample(input: &[u8], implementation: fn(&[u8], &mut Histogram32)) -> Duration {
+    let mut fastest = Duration::MAX;
+    for _ in 0..SAMPLES {
+        let start = Instant::now();
+        for _ in 0..CALLS_PER_SAMPLE {
+            let mut histogram = Histogram32::default();
+            implementation(black_box(input), &mut histogram);
+            black_box(&histogram);
+        }
+        fastest = fastest.min(start.elapsed());
+    }
+    fastest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn returns_plausible_threshold() {
+        let threshold = calibrate_histogram_threshold();
+        assert!(
+            (MIN_THRESHOLD..=MAX_THRESHOLD).contains(&threshold),
+            "got {threshold}"
+        );
+        assert_eq!(threshold % STEP, 0);
+    }
+
+    #[test]
+    fn crossover_picks_first_size_where_batched_is_not_slower() {
+        // Reference: 1ns per byte. Batched: 40ns fixed cost, then 0.5ns per byte; so it's slower
+        // until 80 bytes, and ties exactly at 80.
+        let linear = |size: usize| {
+            let reference = Duration::from_nanos(size as u64);
+            let batched = Duration::from_nanos(40 + size as u64 / 2);
+            (reference, batched)
+        };
+        assert_eq!(crossover(linear), 80);
+
+        let batched_always_faster = |_| (Duration::from_nanos(2), Duration::from_nanos(1));
+        assert_eq!(crossover(batched_always_faster), 16);
+
+        let batched_never_faster = |_| (Duration::from_nanos(1), Duration::from_nanos(2));
+        assert_eq!(crossover(batched_never_faster), 256);
+    }
+}
@@ -0,0 +1,150 @@
+//! Counting helpers which answer questions about byte frequencies without building a full
+//! [`Histogram32`].
+//!
+//! [`Histogram32`]: super::Histogram32
+
+/// Counts how many bytes in `bytes` are members of `set`, in a single pass.
+///
+/// A byte `b` is a member of the set if `set[b as usize]` is `true`. This is cheaper than
+/// building a full histogram and summing a subset of it, when only the subset count is needed,
+/// e.g. to find how much of a buffer is printable ASCII.
+///
+/// # Arguments
+///
+/// * `bytes` - A slice of bytes to process.
+/// * `set` - Lookup table of which byte values to count.
+///
+/// # Returns
+///
+/// The number of bytes in `bytes` for which `set[byte]` is `true`.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::histogram::count_in_set;
+///
+/// let printable: [bool; 256] = core::array::from_fn(|x| (0x20..0x7F).contains(&x));
+/// assert_eq!(count_in_set(b"hi\x00\xFF", &printable), 2);
+/// ```
+pub fn count_in_set(bytes: &[u8], set: &[bool; 256]) -> usize {
+    // Independent accumulators, to avoid serializing on a single add chain.
+    let mut count0 = 0;
+    let mut count1 = 0;
+    let mut count2 = 0;
+    let mut count3 = 0;
+
+    let mut chunks = bytes.chunks_exact(4);
+    for chunk in &mut chunks {
+        count0 += set[chunk[0] as usize] as usize;
+        count1 += set[chunk[1] as usize] as usize;
+        count2 += set[chunk[2] as usize] as usize;
+        count3 += set[chunk[3] as usize] as usize;
+    }
+
+    for &byte in chunks.remainder() {
+        count0 += set[byte as usize] as usize;
+    }
+
+    count0 + count1 + count2 + count3
+}
+
+/// Counts the bytes of a restricted alphabet (`0..=max_symbol`) into a histogram only as large
+/// as that alphabet.
+///
+/// For data known to only use small byte values, e.g. 6-bit symbols with a `max_symbol` of 63;
+/// the counters then take 256 bytes rather than the 1KiB of a [`Histogram32`], which keeps the
+/// working set small. Like [`histogram32_from_bytes`], the counts are added onto those already
+/// in `out`.
+///
+/// # Arguments
+///
+/// * `bytes` - A slice of bytes to process; every byte must be `<= max_symbol`.
+/// * `max_symbol` - The largest byte value in `bytes`.
+/// * `out` - The counts to add onto; `out[x]` is the count of byte value `x`.
+///
+/// # Panics
+///
+/// If `out.len() != max_symbol as usize + 1`, or a byte exceeds `max_symbol`. In debug builds,
+/// the latter is asserted up front with a descriptive message; in release builds, it panics on
+/// the out of bounds counter instead.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::histogram::histogram_subrange_from_bytes;
+///
+/// let mut counts = [0u32; 4];
+/// histogram_subrange_from_bytes(&[0, 3, 3, 1], 3, &mut counts);
+/// assert_eq!(counts, [1, 1, 0, 2]);
+/// ```
+///
+/// [`Histogram32`]: super::Histogram32
+/// [`histogram32_from_bytes`]: super::histogram32_from_bytes
+pub fn histogram_subrange_from_bytes(bytes: &[u8], max_symbol: u8, out: &mut [u32]) {
+    assert_eq!(
+        out.len(),
+        max_symbol as usize + 1,
+        "out length must be max_symbol + 1"
+    );
+    debug_assert!(
+        bytes.iter().all(|&byte| byte <= max_symbol),
+        "byte exceeds max_symbol ({max_symbol})"
+    );
+
+    for &byte in bytes {
+        out[byte as usize] += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::histogram::Histogram32;
+    use std::vec::Vec;
+
+    #[test]
+    fn counts_printable_ascii_in_mixed_buffer() {
+        let printable: [bool; 256] = core::array::from_fn(|x| (0x20..0x7F).contains(&x));
+        let data = b"Hello, World!\x00\x01\x02\xFE\xFF\x7F\tline";
+
+        let expected: usize = Histogram32::from_bytes(data)
+            .counter
+            .iter()
+            .enumerate()
+            .filter(|(byte, _)| printable[*byte])
+            .map(|(_, &count)| count as usize)
+            .sum();
+
+        assert_eq!(count_in_set(data, &printable), 17);
+        assert_eq!(count_in_set(data, &printable), expected);
+    }
+
+    #[test]
+    fn empty_set_and_empty_input_count_zero() {
+        assert_eq!(count_in_set(b"abc", &[false; 256]), 0);
+        assert_eq!(count_in_set(&[], &[true; 256]), 0);
+    }
+
+    #[test]
+    fn subrange_counts_six_bit_data() {
+        let data: Vec<u8> = (0..10_000_u32).map(|x| (x * 7 % 64) as u8).collect();
+        let mut counts = [0u32; 64];
+        histogram_subrange_from_bytes(&data, 63, &mut counts);
+
+        let full = Histogram32::from_bytes(&data);
+        assert_eq!(counts[..], full.counter[..64]);
+        assert_eq!(counts.iter().sum::<u32>(), 10_000);
+    }
+
+    #[test]
+    #[should_panic]
+    fn subrange_rejects_wrong_out_length() {
+        histogram_subrange_from_bytes(&[0, 1], 63, &mut [0u32; 32]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn subrange_rejects_byte_above_max_symbol() {
+        histogram_subrange_from_bytes(&[0, 64], 63, &mut [0u32; 64]);
+    }
+}
@@ -0,0 +1,358 @@
+//! Distances between the byte distributions of two [`Histogram32`]s.
+//!
+//! The histograms are normalized to probabilities first, so histograms of inputs with
+//! different lengths can be compared directly.
+
+use super::Histogram32;
+use crate::math::sqrt;
+#[cfg(feature = "std")]
+use std::vec::Vec;
+
+/// Calculates the total variation distance between the distributions of two histograms.
+///
+/// This is half of the L1 distance between the normalized histograms, `0.5 * Σ |p_a(i) - p_b(i)|`.
+/// It's cheap (256 elements) and easy to interpret: the result is the fraction of probability
+/// mass that would need to move to turn one distribution into the other.
+///
+/// # Returns
+///
+/// A value in `0.0..=1.0`; 0.0 for identical distributions, 1.0 for distributions with no
+/// byte values in common. An empty histogram is only identical to another empty histogram.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::histogram::{l1_distance, Histogram32};
+///
+/// let a = Histogram32::from_bytes(&[0, 0, 1, 1]);
+/// let b = Histogram32::from_bytes(&[0, 1]);
+/// let c = Histogram32::from_bytes(&[2, 3]);
+///
+/// assert_eq!(l1_distance(&a, &b), 0.0);
+/// assert_eq!(l1_distance(&a, &c), 1.0);
+/// ```
+pub fn l1_distance(a: &Histogram32, b: &Histogram32) -> f64 {
+    let total_a = a.total();
+    let total_b = b.total();
+    if total_a == 0 || total_b == 0 {
+        return if total_a == total_b { 0.0 } else { 1.0 };
+    }
+
+    let total_a = total_a as f64;
+    let total_b = total_b as f64;
+    let mut distance = 0.0;
+    for (&count_a, &count_b) in a.counter.iter().zip(b.counter.iter()) {
+        distance += (count_a as f64 / total_a - count_b as f64 / total_b).abs();
+    }
+
+    // Rounding can push the sum marginally past 2.0
+    (distance * 0.5).min(1.0)
+}
+
+/// Calculates the Kolmogorov–Smirnov statistic between the distributions of two histograms.
+///
+/// This is the largest difference between the cumulative distributions, with byte values in
+/// ascending order: `max_i |CDF_a(i) - CDF_b(i)|`. Unlike [`l1_distance`], it takes the
+/// ordering of byte values into account; so mass moving to a neighbouring byte value counts for
+/// less than mass moving across the whole range. Useful for detecting drift in a data stream.
+///
+/// # Returns
+///
+/// A value in `0.0..=1.0`; 0.0 for identical distributions, 1.0 when every byte value in one
+/// histogram is below every byte value in the other. An empty histogram is only identical to
+/// another empty histogram.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::histogram::{ks_statistic, Histogram32};
+///
+/// let a = Histogram32::from_bytes(&[0, 1]);
+/// let b = Histogram32::from_bytes(&[1, 2]);
+///
+/// assert_eq!(ks_statistic(&a, &a), 0.0);
+/// assert_eq!(ks_statistic(&a, &b), 0.5);
+/// ```
+pub fn ks_statistic(a: &Histogram32, b: &Histogram32) -> f64 {
+    let total_a = a.total();
+    let total_b = b.total();
+    if total_a == 0 || total_b == 0 {
+        return if total_a == total_b { 0.0 } else { 1.0 };
+    }
+
+    // Accumulate in integers, so the CDFs end exactly at 1.0 and only the final division rounds.
+    let total_a = total_a as f64;
+    let total_b = total_b as f64;
+    let mut cumulative_a = 0_u64;
+    let mut cumulative_b = 0_u64;
+    let mut max_deviation: f64 = 0.0;
+    for (&count_a, &count_b) in a.counter.iter().zip(b.counter.iter()) {
+        cumulative_a += count_a as u64;
+        cumulative_b += count_b as u64;
+        let deviation = (cumulative_a as f64 / total_a - cumulative_b as f64 / total_b).abs();
+        max_deviation = max_deviation.max(deviation);
+    }
+
+    max_deviation.min(1.0)
+}
+
+/// Calculates the symmetric chi-squared distance between the distributions of two histograms.
+///
+/// This is `0.5 * Σ (p_a(i) - p_b(i))² / (p_a(i) + p_b(i))`, skipping byte values absent from
+/// both. Compared to [`l1_distance`], differences in rare byte values weigh more than the same
+/// absolute difference in common ones.
+///
+/// # Returns
+///
+/// A value in `0.0..=1.0`; 0.0 for identical distributions, 1.0 for distributions with no
+/// byte values in common. An empty histogram is only identical to another empty histogram.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::histogram::{chi_squared_distance, Histogram32};
+///
+/// let a = Histogram32::from_bytes(&[0, 1]);
+/// let b = Histogram32::from_bytes(&[2, 3]);
+///
+/// assert_eq!(chi_squared_distance(&a, &a), 0.0);
+/// assert_eq!(chi_squared_distance(&a, &b), 1.0);
+/// ```
+pub fn chi_squared_distance(a: &Histogram32, b: &Histogram32) -> f64 {
+    let total_a = a.total();
+    let total_b = b.total();
+    if total_a == 0 || total_b == 0 {
+        return if total_a == total_b { 0.0 } else { 1.0 };
+    }
+
+    let total_a = total_a as f64;
+    let total_b = total_b as f64;
+    let mut distance = 0.0;
+    for (&count_a, &count_b) in a.counter.iter().zip(b.counter.iter()) {
+        let p_a = count_a as f64 / total_a;
+        let p_b = count_b as f64 / total_b;
+        if p_a + p_b > 0.0 {
+            distance += (p_a - p_b) * (p_a - p_b) / (p_a + p_b);
+        }
+    }
+
+    (distance * 0.5).min(1.0)
+}
+
+/// Calculates the cosine distance between two histograms, treated as 256 dimensional vectors.
+///
+/// This is `1 - (a · b) / (|a| |b|)`. It only depends on the direction of the vectors, so like
+/// the other distances, the lengths of the inputs don't matter. Dominated by the most common
+/// byte values, as their counts are squared.
+///
+/// # Returns
+///
+/// A value in `0.0..=1.0`; 0.0 for identical distributions, 1.0 for distributions with no
+/// byte values in common. An empty histogram is only identical to another empty histogram.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::histogram::{cosine_distance, Histogram32};
+///
+/// let a = Histogram32::from_bytes(&[0, 1]);
+/// let b = Histogram32::from_bytes(&[0, 0, 1, 1]);
+/// let c = Histogram32::from_bytes(&[2, 3]);
+///
+/// assert!(cosine_distance(&a, &b) < 1e-12);
+/// assert_eq!(cosine_distance(&a, &c), 1.0);
+/// ```
+pub fn cosine_distance(a: &Histogram32, b: &Histogram32) -> f64 {
+    let mut dot = 0.0;
+    let mut norm_a = 0.0;
+    let mut norm_b = 0.0;
+    for (&count_a, &count_b) in a.counter.iter().zip(b.counter.iter()) {
+        let count_a = count_a as f64;
+        let count_b = count_b as f64;
+        dot += count_a * count_b;
+        norm_a += count_a * count_a;
+        norm_b += count_b * count_b;
+    }
+
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return if norm_a == norm_b { 0.0 } else { 1.0 };
+    }
+
+    (1.0 - dot / (sqrt(norm_a) * sqrt(norm_b))).clamp(0.0, 1.0)
+}
+
+/// A distance between the distributions of two histograms, for [`pairwise_distances`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DistanceMetric {
+    /// [`l1_distance`]
+    L1,
+    /// [`chi_squared_distance`]
+    ChiSquared,
+    /// [`cosine_distance`]
+    Cosine,
+    /// [`ks_statistic`]
+    Ks,
+}
+
+impl DistanceMetric {
+    /// Calculates the distance between `a` and `b` using this metric.
+    pub fn distance(self, a: &Histogram32, b: &Histogram32) -> f64 {
+        match self {
+            DistanceMetric::L1 => l1_distance(a, b),
+            DistanceMetric::ChiSquared => chi_squared_distance(a, b),
+            DistanceMetric::Cosine => cosine_distance(a, b),
+            DistanceMetric::Ks => ks_statistic(a, b),
+        }
+    }
+}
+
+/// Calculates the distance between every pair of histograms; e.g. as the input to clustering.
+///
+/// All of the metrics are symmetric and 0.0 between a histogram and itself, so only the pairs
+/// `(i, j)` with `i < j` are computed.
+///
+/// # Output Layout
+///
+/// The upper triangle of the distance matrix, without the diagonal, in row major order:
+/// `(0, 1), (0, 2), ..., (0, n - 1), (1, 2), ..., (n - 2, n - 1)`. That's `n * (n - 1) / 2`
+/// distances; the distance between `i` and `j` (`i < j`) is at index
+/// `i * n - i * (i + 1) / 2 + (j - i - 1)`. (This is the same layout as SciPy's `pdist`.)
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::histogram::{pairwise_distances, DistanceMetric, Histogram32};
+///
+/// let hists = [
+///     Histogram32::from_bytes(&[0, 1]),
+///     Histogram32::from_bytes(&[0, 0, 1, 1]),
+///     Histogram32::from_bytes(&[2, 3]),
+/// ];
+/// let distances = pairwise_distances(&hists, DistanceMetric::L1);
+/// assert_eq!(distances, [0.0, 1.0, 1.0]);
+/// ```
+#[cfg(feature = "std")]
+pub fn pairwise_distances(hists: &[Histogram32], metric: DistanceMetric) -> Vec<f64> {
+    let n = hists.len();
+    let mut distances = Vec::with_capacity(n * n.saturating_sub(1) / 2);
+    for (i, a) in hists.iter().enumerate() {
+        for b in &hists[i + 1..] {
+            distances.push(metric.distance(a, b));
+        }
+    }
+
+    distances
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn l1_distance_of_identical_is_zero() {
+        let a = Histogram32::from_bytes(b"the quick brown fox");
+        assert_eq!(l1_distance(&a, &a), 0.0);
+        assert_eq!(
+            l1_distance(&Histogram32::default(), &Histogram32::default()),
+            0.0
+        );
+    }
+
+    #[test]
+    fn l1_distance_of_disjoint_support_is_one() {
+        let low = Histogram32::from_bytes(&[0, 1, 2, 3, 3]);
+        let high = Histogram32::from_bytes(&[200, 201, 255]);
+        assert!((l1_distance(&low, &high) - 1.0).abs() < 1e-12);
+        assert_eq!(l1_distance(&low, &Histogram32::default()), 1.0);
+    }
+
+    #[test]
+    fn l1_distance_of_partial_overlap() {
+        // a = {0: 0.5, 1: 0.5}, b = {1: 0.5, 2: 0.5}: half the mass moves.
+        let a = Histogram32::from_bytes(&[0, 1]);
+        let b = Histogram32::from_bytes(&[1, 2]);
+        assert!((l1_distance(&a, &b) - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn ks_statistic_of_identical_is_zero() {
+        let a = Histogram32::from_bytes(b"the quick brown fox");
+        assert_eq!(ks_statistic(&a, &a), 0.0);
+        assert_eq!(
+            ks_statistic(&Histogram32::default(), &Histogram32::default()),
+            0.0
+        );
+
+        // Same distribution, different lengths.
+        let b = Histogram32::from_bytes(b"the quick brown foxthe quick brown fox");
+        assert!(ks_statistic(&a, &b) < 1e-12);
+    }
+
+    #[test]
+    fn ks_statistic_of_low_vs_high_bytes_is_one() {
+        let low = Histogram32::from_bytes(&[0, 1, 2, 3, 3, 10]);
+        let high = Histogram32::from_bytes(&[200, 201, 255]);
+        assert!((ks_statistic(&low, &high) - 1.0).abs() < 1e-12);
+        assert!((ks_statistic(&high, &low) - 1.0).abs() < 1e-12);
+        assert_eq!(ks_statistic(&low, &Histogram32::default()), 1.0);
+    }
+
+    #[test]
+    fn ks_statistic_weighs_distance_between_byte_values() {
+        // Both are disjoint from `a`, but `near` only moves the mass by one byte value.
+        let a = Histogram32::from_bytes(&[10, 20]);
+        let near = Histogram32::from_bytes(&[11, 21]);
+        let far = Histogram32::from_bytes(&[30, 40]);
+        assert_eq!(l1_distance(&a, &near), l1_distance(&a, &far));
+        assert_eq!(ks_statistic(&a, &near), 0.5);
+        assert_eq!(ks_statistic(&a, &far), 1.0);
+    }
+
+    #[test]
+    fn chi_squared_and_cosine_bounds() {
+        let a = Histogram32::from_bytes(b"the quick brown fox");
+        let b = Histogram32::from_bytes(&[200, 201, 255]);
+        let empty = Histogram32::default();
+        for distance in [chi_squared_distance, cosine_distance] {
+            assert!(distance(&a, &a) < 1e-12);
+            assert!((distance(&a, &b) - 1.0).abs() < 1e-12);
+            assert_eq!(distance(&a, &empty), 1.0);
+            assert_eq!(distance(&empty, &empty), 0.0);
+        }
+    }
+
+    #[test]
+    fn pairwise_distances_cover_upper_triangle() {
+        let hists = [
+            Histogram32::from_bytes(b"the quick brown fox"),
+            Histogram32::from_bytes(&[0, 1, 2, 3, 3]),
+            Histogram32::from_bytes(b"the lazy dog"),
+            Histogram32::from_bytes(&[3, 2, 1, 0, 3]),
+        ];
+        let n = hists.len();
+
+        for metric in [
+            DistanceMetric::L1,
+            DistanceMetric::ChiSquared,
+            DistanceMetric::Cosine,
+            DistanceMetric::Ks,
+        ] {
+            let distances = pairwise_distances(&hists, metric);
+            assert_eq!(distances.len(), n * (n - 1) / 2);
+
+            for i in 0..n {
+                for j in i + 1..n {
+                    let index = i * n - i * (i + 1) / 2 + (j - i - 1);
+                    assert_eq!(distances[index], metric.distance(&hists[i], &hists[j]));
+                    assert_eq!(distances[index], metric.distance(&hists[j], &hists[i]));
+                }
+            }
+
+            // Hists 1 and 3 hold the same bytes, in a different order; (1, 3) is at index 4.
+            assert!(distances[4] < 1e-12, "{metric:?}");
+        }
+
+        assert!(pairwise_distances(&hists[..1], DistanceMetric::L1).is_empty());
+        assert!(pairwise_distances(&[], DistanceMetric::L1).is_empty());
+    }
+}
@@ -0,0 +1,206 @@
+//! Implementation of a histogram using 16-bit unsigned integers as counters.
+//!
+//! A [Histogram16] is half the size of a [Histogram32] (512 bytes instead of 1KiB), so it's
+//! cheaper to zero and keeps more of the cache free when histogramming many small blocks.
+//! In exchange, it can only count up to 65535 occurrences of each byte.
+//!
+//! [Histogram32]: super::Histogram32
+
+use super::{Histogram, HistogramIter};
+use core::ops::{Deref, DerefMut};
+
+/// Implementation of a histogram using unsigned 16 bit integers as the counter.
+///
+/// Max safe array size to pass is 65,535; use [`Histogram32`] for anything bigger.
+///
+/// [`Histogram32`]: super::Histogram32
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Histogram16 {
+    pub inner: Histogram<u16>,
+}
+
+impl Default for Histogram<u16> {
+    // Defaults to a zero'd array.
+    fn default() -> Self {
+        Histogram { counter: [0; 256] }
+    }
+}
+
+impl Deref for Histogram16 {
+    type Target = Histogram<u16>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl DerefMut for Histogram16 {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+impl<'a> IntoIterator for &'a Histogram16 {
+    type Item = (u8, u16);
+    type IntoIter = HistogramIter<'a, u16>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        (&self.inner).into_iter()
+    }
+}
+
+impl Histogram16 {
+    /// This is a shortcut for [`histogram16_from_bytes`]
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        let mut histogram = Histogram16::default();
+        histogram16_from_bytes(bytes, &mut histogram);
+        histogram
+    }
+}
+
+/// Calculates a new histogram given a byte slice.
+///
+/// The counts are added to the existing counts in `hist`.
+///
+/// # Panics
+///
+/// If `bytes` is longer than 65535 bytes, or any count would exceed 65535 once added to the
+/// existing count in `hist`. See [`histogram16_from_bytes_unchecked`] to skip these checks.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::histogram::{histogram16_from_bytes, Histogram16};
+///
+/// let mut histogram = Histogram16::default();
+/// histogram16_from_bytes(&[1, 2, 3, 1, 2, 1], &mut histogram);
+/// assert_eq!(histogram.counter[1], 3);
+/// ```
+pub fn histogram16_from_bytes(bytes: &[u8], hist: &mut Histogram16) {
+    assert!(
+        bytes.len() <= u16::MAX as usize,
+        "Histogram16 can count at most 65535 bytes, got {}",
+        bytes.len()
+    );
+
+    // Common case: no counter can overflow, even if every byte has the same value.
+    let max_count = hist.inner.counter.iter().copied().max().unwrap_or(0);
+    if max_count as usize + bytes.len() <= u16::MAX as usize {
+        return histogram16_from_bytes_unchecked(bytes, hist);
+    }
+
+    // Otherwise count separately, and check each counter as it's added.
+    let mut added = Histogram16::default();
+    histogram16_from_bytes_unchecked(bytes, &mut added);
+    for (count, &added) in hist.inner.counter.iter_mut().zip(&added.inner.counter) {
+        *count = count
+            .checked_add(added)
+            .expect("Histogram16 counter overflowed");
+    }
+}
+
+/// Calculates a new histogram given a byte slice, without checking whether the counts can
+/// overflow.
+///
+/// Reads 4 bytes at a time, like the batched [`Histogram32`] implementation.
+/// Meant for the hot path of histogramming many small (e.g. <= 255 byte) blocks, where the
+/// caller already knows the block size.
+///
+/// # Remarks
+///
+/// The caller must guarantee that no counter exceeds 65535, e.g. by passing at most 65535 bytes
+/// into a zeroed histogram. Overflowing counters wrap in release builds; debug builds panic.
+///
+/// [`Histogram32`]: super::Histogram32
+pub fn histogram16_from_bytes_unchecked(bytes: &[u8], hist: &mut Histogram16) {
+    debug_assert!(
+        bytes.len() <= u16::MAX as usize,
+        "Histogram16 can count at most 65535 bytes, got {}",
+        bytes.len()
+    );
+
+    let counter = &mut hist.inner.counter;
+    let mut chunks = bytes.chunks_exact(4);
+    for chunk in &mut chunks {
+        let value = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        increment(counter, value as u8);
+        increment(counter, (value >> 8) as u8);
+        increment(counter, (value >> 16) as u8);
+        increment(counter, (value >> 24) as u8);
+    }
+
+    for &byte in chunks.remainder() {
+        increment(counter, byte);
+    }
+}
+
+#[inline(always)]
+fn increment(counter: &mut [u16; 256], byte: u8) {
+    let count = &mut counter[byte as usize];
+    debug_assert!(*count < u16::MAX, "Histogram16 counter overflowed");
+    *count = count.wrapping_add(1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::histogram::Histogram32;
+    use std::vec::Vec;
+
+    #[test]
+    fn matches_histogram32_for_small_blocks() {
+        for size in [0, 1, 3, 4, 5, 255] {
+            let data: Vec<u8> = (0..size).map(|x| (x * 7 % 13) as u8).collect();
+            let mut histogram = Histogram16::default();
+            histogram16_from_bytes_unchecked(&data, &mut histogram);
+
+            let expected = Histogram32::from_bytes(&data);
+            for (byte, count) in &histogram {
+                assert_eq!(count as u32, expected.counter[byte as usize], "size {size}");
+            }
+        }
+    }
+
+    #[test]
+    fn counts_up_to_max() {
+        let data = [9u8; u16::MAX as usize];
+        assert_eq!(Histogram16::from_bytes(&data).counter[9], u16::MAX);
+    }
+
+    #[test]
+    fn checked_accumulates_without_overflow() {
+        let mut histogram = Histogram16::default();
+        histogram16_from_bytes(&[9u8; 30000], &mut histogram);
+        histogram16_from_bytes(&[9u8; 30000], &mut histogram);
+        assert_eq!(histogram.counter[9], 60000);
+
+        // The total may exceed 65535, as long as no single counter does.
+        histogram16_from_bytes(&[1u8; 40000], &mut histogram);
+        assert_eq!(histogram.counter[1], 40000);
+    }
+
+    #[test]
+    #[should_panic(expected = "Histogram16 counter overflowed")]
+    fn checked_rejects_overflow_when_accumulating() {
+        let mut histogram = Histogram16::default();
+        histogram16_from_bytes(&[9u8; 40000], &mut histogram);
+        histogram16_from_bytes(&[9u8; 40000], &mut histogram);
+    }
+
+    #[test]
+    #[should_panic]
+    fn checked_rejects_too_long_input() {
+        let data = [0u8; u16::MAX as usize + 1];
+        Histogram16::from_bytes(&data);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Histogram16 counter overflowed")]
+    fn unchecked_overflow_is_caught_in_debug() {
+        let mut histogram = Histogram16::default();
+        histogram.counter[5] = u16::MAX;
+        histogram16_from_bytes_unchecked(&[5], &mut histogram);
+    }
+}
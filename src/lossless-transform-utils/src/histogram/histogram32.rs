@@ -50,7 +50,7 @@
 //! While some functions in this module use unsafe code internally for performance reasons,
 //! all public interfaces are safe to use from safe Rust code.
 
-use super::Histogram;
+use super::{histogram32_from_bytes_with_threshold, Histogram, HistogramIter};
 use core::ops::{Deref, DerefMut};
 
 /// Implementation of a histogram using unsigned 32 bit integers as the counter.
@@ -84,6 +84,30 @@ impl DerefMut for Histogram32 {
     }
 }
 
+/// Iterates over `(byte, count)` pairs for all 256 byte values, in ascending byte order.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::histogram::Histogram32;
+///
+/// let histogram = Histogram32::from_bytes(&[1, 2, 3, 1, 2, 1]);
+///
+/// let mut sum = 0;
+/// for (_byte, count) in &histogram {
+///     sum += count as u64;
+/// }
+/// assert_eq!(sum, histogram.total());
+/// ```
+impl<'a> IntoIterator for &'a Histogram32 {
+    type Item = (u8, u32);
+    type IntoIter = HistogramIter<'a, u32>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        (&self.inner).into_iter()
+    }
+}
+
 impl Histogram32 {
     /// This is a shortcut for [`histogram32_from_bytes`]
     pub fn from_bytes(bytes: &[u8]) -> Self {
@@ -143,11 +167,38 @@ impl Histogram32 {
 /// it is safe to call and use from safe Rust code.
 pub fn histogram32_from_bytes(bytes: &[u8], hist: &mut Histogram32) {
     // Obtained by benching on a 5900X. May vary with different hardware.
-    if bytes.len() < 64 {
-        histogram32_reference(bytes, hist)
-    } else {
-        histogram32_generic_batched_unroll_4_u32(bytes, hist)
+    histogram32_from_bytes_with_threshold(bytes, hist, DEFAULT_BATCHED_THRESHOLD)
+}
+
+/// The input size (in bytes) from which [`histogram32_from_bytes`] switches from the simple
+/// reference implementation to the batched one.
+///
+/// Obtained by benching on a 5900X. May vary with different hardware.
+pub const DEFAULT_BATCHED_THRESHOLD: usize = 64;
+
+/// Implementation used by [`histogram32_from_bytes`] for inputs above the batched threshold.
+///
+/// Inputs below the threshold always use the simple reference implementation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DispatchKind {
+    /// Portable batched implementation; any CPU.
+    Portable,
+    /// Batched implementation using BMI1 instructions (x86_64, or x86 with `nightly`).
+    Bmi1,
+}
+
+/// Returns the implementation [`histogram32_generic_batched_unroll_4_u32`] uses on the
+/// current machine. Must match the conditions in that function.
+pub(crate) fn batched_dispatch_kind() -> DispatchKind {
+    #[cfg(any(
+        all(target_arch = "x86_64", feature = "std"),
+        all(target_arch = "x86", feature = "nightly", feature = "std")
+    ))]
+    if std::is_x86_feature_detected!("bmi1") {
+        return DispatchKind::Bmi1;
     }
+
+    DispatchKind::Portable
 }
 
 pub(crate) fn histogram32_generic_batched_unroll_4_u32(bytes: &[u8], histogram: &mut Histogram32) {
@@ -464,6 +515,9 @@ mod alternative_implementation_tests {
     #[case::batched_unroll4_u32(histogram32_generic_batched_unroll_4_u32)]
     #[case::batched_unroll4_u64(histogram32_generic_batched_unroll_4_u64)]
     #[case::nonaliased_withruns(histogram_nonaliased_withruns_core)]
+    #[case::nonaliased_8slice(histogram_nonaliased_8slice)]
+    #[case::nonaliased_1slice(histogram_nonaliased_core::<1>)]
+    #[case::nonaliased_3slice(histogram_nonaliased_core::<3>)]
     fn test_against_reference(#[case] implementation: fn(&[u8], &mut Histogram32)) {
         // Test sizes from 0 to 767 bytes
         for size in 0..=767 {
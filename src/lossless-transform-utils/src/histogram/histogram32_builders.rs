@@ -0,0 +1,665 @@
+//! Alternative ways of building a [`Histogram32`]: with a different choice of implementation, or
+//! for inputs that aren't a single flat slice.
+//!
+//! These all add onto the counts already in the histogram, like [`histogram32_from_bytes`].
+
+use super::{
+    histogram32_from_bytes, histogram32_generic_batched_unroll_4_u32, histogram32_reference,
+    Histogram32, DEFAULT_BATCHED_THRESHOLD,
+};
+
+/// The L1 data cache size [`DEFAULT_BATCHED_THRESHOLD`] was tuned on (5900X).
+const DEFAULT_THRESHOLD_L1_BYTES: usize = 32 * 1024;
+
+/// Calculates a new histogram given a byte slice, with a custom threshold for switching
+/// from the simple reference implementation to the batched one.
+///
+/// This is [`histogram32_from_bytes`], with the hardcoded [`DEFAULT_BATCHED_THRESHOLD`]
+/// replaced by a caller-provided value, e.g. one measured on the machine the code runs on.
+///
+/// # Arguments
+///
+/// * `bytes` - A slice of bytes to process.
+/// * `hist` - The histogram to add the counts to.
+/// * `threshold` - Inputs with at least this many bytes use the batched implementation.
+pub fn histogram32_from_bytes_with_threshold(
+    bytes: &[u8],
+    hist: &mut Histogram32,
+    threshold: usize,
+) {
+    #[cfg(feature = "instrument")]
+    let start = std::time::Instant::now();
+
+    if bytes.len() < threshold {
+        histogram32_reference(bytes, hist)
+    } else {
+        histogram32_generic_batched_unroll_4_u32(bytes, hist)
+    }
+
+    #[cfg(feature = "instrument")]
+    crate::instrument::record_histogram(start, bytes.len());
+}
+
+/// Calculates a new histogram given a byte slice, tuning the implementation choice for a
+/// core with the given L1 data cache size.
+///
+/// On hybrid CPUs (Intel P/E cores, ARM big.LITTLE) the cores differ in cache size, which
+/// CPU feature detection doesn't capture. A scheduler aware caller can pass the L1 size of
+/// the core it is running on; the threshold is then scaled inversely with the L1 size,
+/// relative to the 32K the default was tuned on. This scaling is a heuristic, not measured:
+/// only the default threshold was benchmarked, on a single machine. If you can measure on the
+/// target cores, prefer [`histogram32_from_bytes_with_threshold`] with the measured value.
+///
+/// This is advisory only; the result is identical to [`histogram32_from_bytes`] for any
+/// `l1_bytes`, only the speed differs.
+///
+/// # Arguments
+///
+/// * `bytes` - A slice of bytes to process.
+/// * `hist` - The histogram to add the counts to.
+/// * `l1_bytes` - Size of the L1 data cache of the current core, in bytes.
+pub fn histogram32_from_bytes_for_cache(bytes: &[u8], hist: &mut Histogram32, l1_bytes: usize) {
+    histogram32_from_bytes_with_threshold(bytes, hist, batched_threshold_for_cache(l1_bytes))
+}
+
+/// Heuristically scales [`DEFAULT_BATCHED_THRESHOLD`] inversely with the L1 size, within 4x of
+/// the default.
+#[inline]
+fn batched_threshold_for_cache(l1_bytes: usize) -> usize {
+    let scaled = DEFAULT_BATCHED_THRESHOLD * DEFAULT_THRESHOLD_L1_BYTES / l1_bytes.max(1);
+    scaled.clamp(DEFAULT_BATCHED_THRESHOLD / 4, DEFAULT_BATCHED_THRESHOLD * 4)
+}
+
+/// Calculates a new histogram given a byte slice, always using the simple portable
+/// reference implementation.
+///
+/// Unlike [`histogram32_from_bytes`], this never uses the batched (or BMI1 assembly)
+/// implementations, regardless of input size or CPU features. The result is always identical,
+/// so this is useful for differential testing at runtime: e.g. asserting that
+/// [`histogram32_from_bytes`] agrees with this on the current machine. It is much slower on
+/// large inputs; don't use it outside of tests.
+///
+/// # Arguments
+///
+/// * `bytes` - A slice of bytes to process.
+/// * `hist` - The histogram to add the counts to.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::histogram::{histogram32_from_bytes_portable, Histogram32};
+///
+/// let data = [1u8, 2, 3, 1].repeat(1000);
+/// let mut portable = Histogram32::default();
+/// histogram32_from_bytes_portable(&data, &mut portable);
+///
+/// assert_eq!(portable.counter, Histogram32::from_bytes(&data).counter);
+/// ```
+pub fn histogram32_from_bytes_portable(bytes: &[u8], hist: &mut Histogram32) {
+    histogram32_reference(bytes, hist)
+}
+
+/// Calculates a histogram of a fixed size block.
+///
+/// Same as [`histogram32_from_bytes`], but as the length is known at compile time, the choice
+/// between the reference and batched implementations is made at compile time too; so there is
+/// no runtime branching on the length.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::histogram::{histogram32_from_array, Histogram32};
+///
+/// let block = [7u8; 4096];
+/// let mut histogram = Histogram32::default();
+/// histogram32_from_array(&block, &mut histogram);
+/// assert_eq!(histogram.counter[7], 4096);
+/// ```
+#[inline(always)]
+pub fn histogram32_from_array<const N: usize>(bytes: &[u8; N], hist: &mut Histogram32) {
+    #[cfg(feature = "instrument")]
+    let start = std::time::Instant::now();
+
+    if N < DEFAULT_BATCHED_THRESHOLD {
+        histogram32_reference(bytes, hist)
+    } else {
+        histogram32_generic_batched_unroll_4_u32(bytes, hist)
+    }
+
+    #[cfg(feature = "instrument")]
+    crate::instrument::record_histogram(start, N);
+}
+
+/// Calculates a histogram of a 2D image with padded rows.
+///
+/// Image rows are often padded for alignment, so that each row starts `stride` bytes after the
+/// previous one, but only `width` of those bytes are pixel data. Histogramming the whole buffer
+/// would include the padding; this counts only the first `width` bytes of each of the `height`
+/// rows. Each row uses [`histogram32_from_bytes`], so long rows get the batched implementation.
+///
+/// # Arguments
+///
+/// * `data` - The image data, starting at the first row.
+/// * `width` - Number of data bytes in each row (pixel width * bytes per pixel).
+/// * `height` - Number of rows.
+/// * `stride` - Distance in bytes between the starts of two consecutive rows. Unused (and may be
+///   anything, including 0) if `height` is 1.
+/// * `hist` - The histogram to add the counts to.
+///
+/// # Panics
+///
+/// If `stride < width` (with more than 1 row), or `data` is too short to contain `height` rows
+/// (including when that size overflows `usize`). The last row does not need to include its
+/// padding.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::histogram::{histogram32_from_image, Histogram32};
+///
+/// // 2 rows of 3 bytes, each padded to 4 bytes with 0xFF.
+/// let image = [1, 2, 3, 0xFF, 4, 5, 6, 0xFF];
+/// let mut histogram = Histogram32::default();
+/// histogram32_from_image(&image, 3, 2, 4, &mut histogram);
+///
+/// assert_eq!(histogram.counter[0xFF], 0);
+/// assert_eq!(histogram.total(), 6);
+/// ```
+pub fn histogram32_from_image(
+    data: &[u8],
+    width: usize,
+    height: usize,
+    stride: usize,
+    hist: &mut Histogram32,
+) {
+    if height == 0 || width == 0 {
+        return;
+    }
+
+    assert!(
+        height == 1 || stride >= width,
+        "stride ({stride}) must be at least width ({width})"
+    );
+    // An image too large to address can't fit in `data` either.
+    let required = (height - 1)
+        .checked_mul(stride)
+        .and_then(|last_row| last_row.checked_add(width));
+    let required = match required {
+        Some(required) if data.len() >= required => required,
+        _ => panic!(
+            "image of {height} rows of {width} bytes, {stride} apart, exceeds data of {} bytes",
+            data.len()
+        ),
+    };
+
+    // A single row has no stride to follow; its stride may be anything, even 0.
+    // Contiguous rows are a single flat buffer; no need to split them either.
+    if height == 1 || stride == width {
+        histogram32_from_bytes(&data[..required], hist);
+        return;
+    }
+
+    for row in data[..required].chunks(stride) {
+        histogram32_from_bytes(&row[..width], hist);
+    }
+}
+
+/// Calculates a histogram of `bytes`, excluding any leading and trailing runs of `trim_byte`.
+///
+/// Sparse files and zero padded buffers often start or end with long runs of a single byte
+/// (usually `0x00`), which dominate the histogram and hide the distribution of the actual data.
+/// Occurrences of `trim_byte` in the middle of the data are still counted.
+///
+/// # Arguments
+///
+/// * `bytes` - The input data.
+/// * `trim_byte` - The byte value whose leading and trailing runs are skipped.
+/// * `hist` - The histogram to add the counts of the untrimmed bytes to.
+///
+/// # Returns
+///
+/// The total number of bytes trimmed from both ends; i.e. `bytes.len()` minus the number of
+/// bytes counted. If `bytes` consists only of `trim_byte`, everything is trimmed.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::histogram::{histogram32_from_bytes_trim_runs, Histogram32};
+///
+/// let data = [0, 0, 0, 1, 0, 2, 0, 0];
+/// let mut histogram = Histogram32::default();
+/// let trimmed = histogram32_from_bytes_trim_runs(&data, 0, &mut histogram);
+///
+/// assert_eq!(trimmed, 5);
+/// assert_eq!(histogram.counter[0], 1);
+/// assert_eq!(histogram.total(), 3);
+/// ```
+pub fn histogram32_from_bytes_trim_runs(
+    bytes: &[u8],
+    trim_byte: u8,
+    hist: &mut Histogram32,
+) -> usize {
+    let start = bytes
+        .iter()
+        .position(|&byte| byte != trim_byte)
+        .unwrap_or(bytes.len());
+    let end = bytes[start..]
+        .iter()
+        .rposition(|&byte| byte != trim_byte)
+        .map_or(start, |last| start + last + 1);
+
+    histogram32_from_bytes(&bytes[start..end], hist);
+    bytes.len() - (end - start)
+}
+
+/// Calculates a separate histogram for each plane (channel) of interleaved data.
+///
+/// Byte `i` is counted into `hists[i % num_planes]`; e.g. with RGBA pixels and `num_planes`
+/// of 4, `hists[0]` receives the red channel, `hists[1]` green, and so on.
+///
+/// # Arguments
+///
+/// * `bytes` - The interleaved data. Need not be a multiple of `num_planes` in length.
+/// * `num_planes` - Number of interleaved planes.
+/// * `hists` - One histogram per plane, to add the counts to.
+///
+/// # Panics
+///
+/// If `num_planes` is 0, or `hists.len() != num_planes`.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::histogram::{histogram32_planar, Histogram32};
+///
+/// // 2 RGB pixels.
+/// let pixels = [255, 0, 0, 255, 128, 0];
+/// let mut hists = [Histogram32::default(); 3];
+/// histogram32_planar(&pixels, 3, &mut hists);
+///
+/// assert_eq!(hists[0].counter[255], 2);
+/// assert_eq!(hists[1].counter[128], 1);
+/// assert_eq!(hists[2].counter[0], 2);
+/// ```
+pub fn histogram32_planar(bytes: &[u8], num_planes: usize, hists: &mut [Histogram32]) {
+    assert!(num_planes > 0, "num_planes must be non-zero");
+    assert_eq!(
+        hists.len(),
+        num_planes,
+        "hists length must equal num_planes"
+    );
+
+    let mut chunks = bytes.chunks_exact(num_planes);
+    for chunk in &mut chunks {
+        for (hist, &byte) in hists.iter_mut().zip(chunk) {
+            hist.counter[byte as usize] += 1;
+        }
+    }
+
+    for (hist, &byte) in hists.iter_mut().zip(chunks.remainder()) {
+        hist.counter[byte as usize] += 1;
+    }
+}
+
+/// Calculates a histogram of `f(byte)` for each byte of `bytes`, without transforming the
+/// buffer first.
+///
+/// Handy for trying out simple byte transforms (e.g. an XOR key or an offset) before writing
+/// them properly. Unlike [`histogram32_from_bytes`], this always uses a simple per byte loop;
+/// the batched implementation extracts several bytes from each loaded word at once, which an
+/// arbitrary closure can't be fitted into. Expect it to be noticeably slower on large inputs.
+///
+/// # Arguments
+///
+/// * `bytes` - The input data.
+/// * `f` - The transform applied to each byte before it is counted.
+/// * `hist` - The histogram to add the counts to.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::histogram::{histogram32_from_bytes_transformed, Histogram32};
+///
+/// let mut histogram = Histogram32::default();
+/// histogram32_from_bytes_transformed(&[1, 2, 3], |byte| byte ^ 1, &mut histogram);
+/// assert_eq!(histogram.counter[0], 1);
+/// assert_eq!(histogram.counter[2], 1);
+/// assert_eq!(histogram.counter[3], 1);
+/// ```
+pub fn histogram32_from_bytes_transformed(
+    bytes: &[u8],
+    f: impl Fn(u8) -> u8,
+    hist: &mut Histogram32,
+) {
+    for &byte in bytes {
+        hist.counter[f(byte) as usize] += 1;
+    }
+}
+
+/// Calculates a histogram of the XOR of two buffers, `a[i] ^ b[i]`.
+///
+/// For estimating how well a patch between two versions of some data would compress: where
+/// the buffers match, the XOR is 0; so a near identical pair gives a histogram dominated by 0,
+/// with a low entropy.
+///
+/// Bytes are XORed 8 at a time, and each byte of the result counted; in a single pass over
+/// both buffers.
+///
+/// # Arguments
+///
+/// * `a` - The first buffer, e.g. the old version.
+/// * `b` - The second buffer, e.g. the new version.
+/// * `hist` - The histogram to add the counts to.
+///
+/// # Unequal Lengths
+///
+/// Only the first `min(a.len(), b.len())` bytes are counted; the bytes past the end of the
+/// shorter buffer have nothing to be XORed with, and are ignored. Histogram them separately
+/// (e.g. with [`histogram32_from_bytes`]) if they matter.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::histogram::{xor_delta_histogram, Histogram32};
+///
+/// let mut histogram = Histogram32::default();
+/// xor_delta_histogram(b"hello world", b"hello there", &mut histogram);
+/// assert_eq!(histogram.counter[0], 6);
+/// assert_eq!(histogram.total(), 11);
+/// ```
+pub fn xor_delta_histogram(a: &[u8], b: &[u8], hist: &mut Histogram32) {
+    let len = a.len().min(b.len());
+    let mut chunks_a = a[..len].chunks_exact(8);
+    let mut chunks_b = b[..len].chunks_exact(8);
+
+    for (chunk_a, chunk_b) in (&mut chunks_a).zip(&mut chunks_b) {
+        let xor = u64::from_le_bytes(chunk_a.try_into().unwrap())
+            ^ u64::from_le_bytes(chunk_b.try_into().unwrap());
+        for byte in xor.to_le_bytes() {
+            hist.counter[byte as usize] += 1;
+        }
+    }
+
+    for (&byte_a, &byte_b) in chunks_a.remainder().iter().zip(chunks_b.remainder()) {
+        hist.counter[(byte_a ^ byte_b) as usize] += 1;
+    }
+}
+
+/// Calculates a histogram, and returns the smallest and largest byte values in `bytes`.
+///
+/// The range is derived from the histogram afterwards, rather than tracked in the counting
+/// loop; so the histogram is built at full [`histogram32_from_bytes`] speed, and finding the
+/// range only costs a scan over the 256 counters instead of a second pass over the input.
+///
+/// Only counts added by this call are considered, so counts already in `hist` (e.g. from
+/// earlier blocks) don't widen the range.
+///
+/// # Returns
+///
+/// `Some((min, max))` of the byte values present in `bytes`; [`None`] if `bytes` is empty.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::histogram::{histogram32_from_bytes_with_range, Histogram32};
+///
+/// let mut histogram = Histogram32::default();
+/// let range = histogram32_from_bytes_with_range(&[9, 3, 200, 3], &mut histogram);
+/// assert_eq!(range, Some((3, 200)));
+/// assert_eq!(histogram.counter[3], 2);
+///
+/// assert_eq!(histogram32_from_bytes_with_range(&[], &mut histogram), None);
+/// ```
+pub fn histogram32_from_bytes_with_range(bytes: &[u8], hist: &mut Histogram32) -> Option<(u8, u8)> {
+    let before = hist.counter;
+    histogram32_from_bytes(bytes, hist);
+
+    let changed = |&byte: &usize| hist.counter[byte] != before[byte];
+    let min = (0..256).find(changed)?;
+    let max = (0..256).rfind(changed)?;
+    Some((min as u8, max as u8))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::random_bytes;
+    use std::vec;
+    use std::vec::Vec;
+
+    fn assert_array_matches_slice<const N: usize>() {
+        let block: [u8; N] = core::array::from_fn(|x| (x * 13 % 256) as u8);
+        let mut from_array = Histogram32::default();
+        let mut from_slice = Histogram32::default();
+        histogram32_from_array(&block, &mut from_array);
+        histogram32_from_bytes(&block, &mut from_slice);
+        assert_eq!(from_array.counter, from_slice.counter, "N = {N}");
+    }
+
+    #[test]
+    fn array_matches_slice_for_various_sizes() {
+        assert_array_matches_slice::<0>();
+        assert_array_matches_slice::<4>();
+        assert_array_matches_slice::<63>();
+        assert_array_matches_slice::<64>();
+        assert_array_matches_slice::<4096>();
+    }
+
+    #[test]
+    fn image_excludes_row_padding() {
+        let width = 100;
+        let height = 7;
+        let stride = 128;
+
+        // Pixel data cycles through 0..200, padding is 0xFF (which pixel data never uses).
+        let mut data = Vec::new();
+        let mut rows_only = Vec::new();
+        for row in 0..height {
+            let pixels: Vec<u8> = (0..width)
+                .map(|x| ((row * width + x) % 200) as u8)
+                .collect();
+            data.extend_from_slice(&pixels);
+            rows_only.extend_from_slice(&pixels);
+            data.resize(data.len() + stride - width, 0xFF);
+        }
+
+        let mut image = Histogram32::default();
+        histogram32_from_image(&data, width, height, stride, &mut image);
+        let naive = Histogram32::from_bytes(&data);
+
+        assert_eq!(image.counter, Histogram32::from_bytes(&rows_only).counter);
+        assert_eq!(image.counter[0xFF], 0);
+        assert_eq!(naive.counter[0xFF] as usize, height * (stride - width));
+    }
+
+    #[test]
+    fn image_with_unpadded_last_row_and_no_padding() {
+        let data: Vec<u8> = (0..=255).collect();
+
+        let mut padded = Histogram32::default();
+        histogram32_from_image(&data[..250], 10, 25, 10, &mut padded);
+        assert_eq!(
+            padded.counter,
+            Histogram32::from_bytes(&data[..250]).counter
+        );
+
+        // Last row ends right after its data.
+        let mut last_row = Histogram32::default();
+        histogram32_from_image(&data[..42], 2, 3, 20, &mut last_row);
+        assert_eq!(last_row.total(), 6);
+        assert_eq!(last_row.counter[40], 1);
+        assert_eq!(last_row.counter[41], 1);
+    }
+
+    #[test]
+    fn trim_runs_skips_zero_padding_on_both_ends() {
+        let payload: Vec<u8> = (0..1000).map(|x| (x % 7) as u8).collect();
+        let mut data = vec![0u8; 4096];
+        data.extend_from_slice(&[1, 2, 3]);
+        data.extend_from_slice(&payload);
+        data.extend_from_slice(&[4, 5, 6]);
+        data.resize(data.len() + 8192, 0);
+
+        let mut histogram = Histogram32::default();
+        let trimmed = histogram32_from_bytes_trim_runs(&data, 0, &mut histogram);
+
+        let mut middle = vec![1, 2, 3];
+        middle.extend_from_slice(&payload);
+        middle.extend_from_slice(&[4, 5, 6]);
+        assert_eq!(trimmed, 4096 + 8192);
+        assert_eq!(histogram.counter, Histogram32::from_bytes(&middle).counter);
+        // Zeros inside the payload are still counted.
+        assert_eq!(histogram.counter[0], 143);
+    }
+
+    #[test]
+    fn trim_runs_of_only_trim_byte_counts_nothing() {
+        let mut histogram = Histogram32::default();
+        assert_eq!(
+            histogram32_from_bytes_trim_runs(&[9; 100], 9, &mut histogram),
+            100
+        );
+        assert_eq!(histogram32_from_bytes_trim_runs(&[], 9, &mut histogram), 0);
+        assert_eq!(histogram.total(), 0);
+    }
+
+    #[test]
+    fn planar_splits_rgba_channels() {
+        // 1000 RGBA pixels plus a trailing partial pixel (R and G only).
+        let mut data = Vec::new();
+        for pixel in 0..1000_u32 {
+            data.extend_from_slice(&[(pixel % 256) as u8, 10, (pixel % 3) as u8, 255]);
+        }
+        data.extend_from_slice(&[7, 10]);
+
+        let mut hists = [Histogram32::default(); 4];
+        histogram32_planar(&data, 4, &mut hists);
+
+        for (plane, hist) in hists.iter().enumerate() {
+            let channel: Vec<u8> = data.iter().skip(plane).step_by(4).copied().collect();
+            assert_eq!(hist.counter, Histogram32::from_bytes(&channel).counter);
+        }
+
+        assert_eq!(hists[0].total(), 1001);
+        assert_eq!(hists[1].counter[10], 1001);
+        assert_eq!(hists[2].total(), 1000);
+        assert_eq!(hists[3].counter[255], 1000);
+    }
+
+    #[test]
+    fn transformed_matches_pretransformed_buffer() {
+        let data: Vec<u8> = (0..10_000_u32).map(|x| (x * 7 % 251) as u8).collect();
+        let shifted: Vec<u8> = data.iter().map(|byte| byte.wrapping_add(1)).collect();
+
+        let mut histogram = Histogram32::default();
+        histogram32_from_bytes_transformed(&data, |byte| byte.wrapping_add(1), &mut histogram);
+        assert_eq!(histogram.counter, Histogram32::from_bytes(&shifted).counter);
+        assert_eq!(histogram.counter[0], 0);
+    }
+
+    #[test]
+    fn xor_delta_of_identical_buffers_is_all_zero() {
+        let data: Vec<u8> = (0..1003_u32).map(|x| (x * 7 % 256) as u8).collect();
+        let mut histogram = Histogram32::default();
+        xor_delta_histogram(&data, &data, &mut histogram);
+
+        assert_eq!(histogram.counter[0], 1003);
+        assert_eq!(histogram.total(), 1003);
+    }
+
+    #[test]
+    fn xor_delta_matches_naive_over_common_prefix() {
+        let a: Vec<u8> = (0..1000_u32).map(|x| (x * 7 % 256) as u8).collect();
+        let b: Vec<u8> = (0..1013_u32).map(|x| (x * 13 % 251) as u8).collect();
+        let xor: Vec<u8> = a.iter().zip(&b).map(|(x, y)| x ^ y).collect();
+
+        for (a, b) in [(&a, &b), (&b, &a)] {
+            let mut histogram = Histogram32::default();
+            xor_delta_histogram(a, b, &mut histogram);
+            assert_eq!(histogram.counter, Histogram32::from_bytes(&xor).counter);
+        }
+    }
+
+    #[test]
+    fn with_range_reports_min_and_max_of_new_bytes() {
+        let mut histogram = Histogram32::default();
+        histogram32_from_bytes(&[0, 255], &mut histogram);
+
+        let data: Vec<u8> = (0..10_000).map(|x| (x % 150 + 40) as u8).collect();
+        let range = histogram32_from_bytes_with_range(&data, &mut histogram);
+        assert_eq!(range, Some((40, 189)));
+        assert_eq!(histogram.total(), 10_002);
+    }
+
+    #[test]
+    #[should_panic]
+    fn planar_rejects_mismatched_hists() {
+        histogram32_planar(&[0; 16], 4, &mut [Histogram32::default(); 3]);
+    }
+
+    #[test]
+    fn single_row_image_ignores_stride() {
+        let data: Vec<u8> = (0..=255).collect();
+        let expected = Histogram32::from_bytes(&data[..100]);
+
+        for stride in [0, 1, 99, 100, 1000] {
+            let mut image = Histogram32::default();
+            histogram32_from_image(&data, 100, 1, stride, &mut image);
+            assert_eq!(image.counter, expected.counter, "stride {stride}");
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn image_rejects_short_data() {
+        histogram32_from_image(&[0; 10], 4, 3, 4, &mut Histogram32::default());
+    }
+
+    #[test]
+    #[should_panic]
+    fn image_rejects_size_overflow() {
+        histogram32_from_image(&[0; 10], 4, usize::MAX, 4, &mut Histogram32::default());
+    }
+
+    #[test]
+    fn portable_matches_dispatched_on_random_data() {
+        let data = random_bytes((1 << 16) + 13, 12345);
+
+        for size in [0, 1, 15, 63, 64, 65, 1000, 4096, 4099, data.len()] {
+            let mut portable = Histogram32::default();
+            histogram32_from_bytes_portable(&data[..size], &mut portable);
+            assert_eq!(
+                portable.counter,
+                Histogram32::from_bytes(&data[..size]).counter,
+                "size {size}"
+            );
+        }
+    }
+
+    #[test]
+    fn for_cache_with_tiny_l1_matches_reference() {
+        for size in [0, 1, 15, 16, 63, 64, 255, 256, 1000] {
+            let input: Vec<u8> = (0..size).map(|x| (x * 7) as u8).collect();
+            for l1_bytes in [0, 1, 4096, 32 * 1024, 1 << 20] {
+                let mut result = Histogram32::default();
+                let mut reference = Histogram32::default();
+                histogram32_from_bytes_for_cache(&input, &mut result, l1_bytes);
+                histogram32_reference(&input, &mut reference);
+                assert_eq!(
+                    result.counter, reference.counter,
+                    "size {size} l1 {l1_bytes}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn threshold_shrinks_as_cache_grows() {
+        assert_eq!(
+            batched_threshold_for_cache(32 * 1024),
+            DEFAULT_BATCHED_THRESHOLD
+        );
+        assert!(batched_threshold_for_cache(16 * 1024) > DEFAULT_BATCHED_THRESHOLD);
+        assert!(batched_threshold_for_cache(64 * 1024) < DEFAULT_BATCHED_THRESHOLD);
+    }
+}
@@ -0,0 +1,151 @@
+//! Conversions between [`Histogram32`] and other representations of its counts.
+
+use super::{Histogram, Histogram32, HistogramError};
+
+impl Histogram32 {
+    /// Serializes the counts as 256 little endian [`u32`]s, in byte order.
+    ///
+    /// The layout is the same on every platform, so it can be stored on disk and read back with
+    /// [`Histogram32::from_le_bytes`] anywhere.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lossless_transform_utils::histogram::Histogram32;
+    ///
+    /// let histogram = Histogram32::from_bytes(&[1, 2, 3, 1]);
+    /// let bytes = histogram.to_le_bytes();
+    /// assert_eq!(bytes[4..8], [2, 0, 0, 0]);
+    /// assert_eq!(Histogram32::from_le_bytes(&bytes).counter, histogram.counter);
+    /// ```
+    pub fn to_le_bytes(&self) -> [u8; 1024] {
+        let mut bytes = [0u8; 1024];
+        for (chunk, count) in bytes.chunks_exact_mut(4).zip(&self.inner.counter) {
+            chunk.copy_from_slice(&count.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Deserializes counts written by [`Histogram32::to_le_bytes`].
+    pub fn from_le_bytes(bytes: &[u8; 1024]) -> Self {
+        let mut histogram = Histogram32::default();
+        let counts = histogram.inner.counter.iter_mut();
+        for (count, chunk) in counts.zip(bytes.chunks_exact(4)) {
+            *count = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        }
+        histogram
+    }
+    /// Copies the counts into a caller owned array.
+    ///
+    /// For hot export paths; writes straight into `out`, rather than returning a new array
+    /// whose copy may or may not be elided.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lossless_transform_utils::histogram::Histogram32;
+    ///
+    /// let histogram = Histogram32::from_bytes(&[1, 2, 2]);
+    /// let mut counts = [0u32; 256];
+    /// histogram.write_counts_to(&mut counts);
+    /// assert_eq!(counts[2], 2);
+    /// ```
+    #[inline]
+    pub fn write_counts_to(&self, out: &mut [u32; 256]) {
+        out.copy_from_slice(&self.counter);
+    }
+}
+
+/// Copies exactly 256 counts into a new histogram; for interop code which receives the counts
+/// as a dynamically sized slice.
+///
+/// # Errors
+///
+/// Returns [`HistogramError::InvalidLength`] if the slice doesn't hold exactly 256 counts,
+/// rather than panicking.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::histogram::{Histogram32, HistogramError};
+///
+/// let counts = vec![1u32; 256];
+/// let histogram = Histogram32::try_from(counts.as_slice()).unwrap();
+/// assert_eq!(histogram.total(), 256);
+///
+/// let result = Histogram32::try_from(&counts[..255]);
+/// assert!(matches!(result, Err(HistogramError::InvalidLength(255))));
+/// ```
+impl TryFrom<&[u32]> for Histogram32 {
+    type Error = HistogramError;
+
+    fn try_from(counts: &[u32]) -> Result<Self, Self::Error> {
+        let counter = counts
+            .try_into()
+            .map_err(|_| HistogramError::InvalidLength(counts.len()))?;
+        Ok(Histogram32 {
+            inner: Histogram { counter },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec;
+    use std::vec::Vec;
+
+    #[test]
+    fn try_from_slice_of_256_copies_counts() {
+        let counts: [u32; 256] = core::array::from_fn(|x| x as u32 * 3);
+        let histogram = Histogram32::try_from(&counts[..]).unwrap();
+        assert_eq!(histogram.counter, counts);
+    }
+
+    #[test]
+    fn try_from_slice_rejects_other_lengths() {
+        let counts = vec![0u32; 512];
+        for len in [0, 1, 255, 257, 512] {
+            let result = Histogram32::try_from(&counts[..len]);
+            assert!(
+                matches!(result, Err(HistogramError::InvalidLength(x)) if x == len),
+                "len {len}"
+            );
+        }
+    }
+
+    #[test]
+    fn write_counts_to_copies_all_counters() {
+        let data: Vec<u8> = (0..5000_u32).map(|x| (x * x % 251) as u8).collect();
+        let histogram = Histogram32::from_bytes(&data);
+
+        let mut out = [u32::MAX; 256];
+        histogram.write_counts_to(&mut out);
+        assert_eq!(out, histogram.counter);
+    }
+
+    #[test]
+    fn le_bytes_round_trip() {
+        let mut histogram = Histogram32::default();
+        for (byte, count) in histogram.counter.iter_mut().enumerate() {
+            *count = (byte as u32).wrapping_mul(0x9E3779B1);
+        }
+        histogram.counter[255] = u32::MAX;
+
+        let restored = Histogram32::from_le_bytes(&histogram.to_le_bytes());
+        assert_eq!(restored.counter, histogram.counter);
+    }
+
+    #[test]
+    fn le_bytes_layout_is_fixed() {
+        let mut histogram = Histogram32::default();
+        histogram.counter[1] = 0x0403_0201;
+        histogram.counter[255] = 0xAABB_CCDD;
+
+        let bytes = histogram.to_le_bytes();
+        assert_eq!(bytes[..4], [0, 0, 0, 0]);
+        assert_eq!(bytes[4..8], [0x01, 0x02, 0x03, 0x04]);
+        assert_eq!(bytes[1020..], [0xDD, 0xCC, 0xBB, 0xAA]);
+        assert_eq!(bytes.iter().filter(|&&x| x != 0).count(), 8);
+    }
+}
@@ -0,0 +1,346 @@
+//! Arithmetic for combining [`Histogram32`] instances, and slice access to their counts.
+//!
+//! Counts use wrapping arithmetic, like the counting routines themselves; a histogram only
+//! overflows if more than 4GiB of data has been counted into it.
+
+use super::{histogram32_from_bytes, Histogram32};
+use core::ops::AddAssign;
+
+impl Histogram32 {
+    /// Returns the counts as a slice, for APIs which take `&[u32]` rather than `&[u32; 256]`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lossless_transform_utils::histogram::Histogram32;
+    ///
+    /// let histogram = Histogram32::from_bytes(&[1, 2, 3, 1]);
+    /// assert_eq!(histogram.counter_slice().iter().sum::<u32>(), 4);
+    /// ```
+    pub fn counter_slice(&self) -> &[u32] {
+        &self.inner.counter
+    }
+
+    /// Returns the counts as a mutable slice, for APIs which take `&mut [u32]` rather than
+    /// `&mut [u32; 256]`.
+    pub fn counter_slice_mut(&mut self) -> &mut [u32] {
+        &mut self.inner.counter
+    }
+
+    /// Adds the counts of `other` into `self`.
+    ///
+    /// This is how partial histograms, e.g. ones computed over separate blocks or on separate
+    /// threads, are combined into the histogram of the full input.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lossless_transform_utils::histogram::Histogram32;
+    ///
+    /// let mut histogram = Histogram32::from_bytes(&[1, 2]);
+    /// histogram.merge(&Histogram32::from_bytes(&[2, 3]));
+    /// assert_eq!(histogram.counter, Histogram32::from_bytes(&[1, 2, 2, 3]).counter);
+    /// ```
+    pub fn merge(&mut self, other: &Histogram32) {
+        for (count, &other) in self.counter.iter_mut().zip(other.counter.iter()) {
+            *count = count.wrapping_add(other);
+        }
+    }
+
+    /// Subtracts the counts of `other` from `self`, wrapping on underflow.
+    ///
+    /// This is the inverse of [`Histogram32::merge`], e.g. for removing a block that slid out
+    /// of a window. `other` should be a subset of `self` (i.e. have been merged into it); if it
+    /// isn't, use [`Histogram32::saturating_sub`] instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lossless_transform_utils::histogram::Histogram32;
+    ///
+    /// let mut histogram = Histogram32::from_bytes(&[1, 2, 2, 3]);
+    /// histogram.subtract(&Histogram32::from_bytes(&[2, 3]));
+    /// assert_eq!(histogram.counter, Histogram32::from_bytes(&[1, 2]).counter);
+    /// ```
+    pub fn subtract(&mut self, other: &Histogram32) {
+        for (count, &other) in self.counter.iter_mut().zip(other.counter.iter()) {
+            *count = count.wrapping_sub(other);
+        }
+    }
+
+    /// Subtracts the counts of `other` from `self`, clamping each count at zero.
+    ///
+    /// Unlike [`Histogram32::subtract`], this is safe to use when `other` is not strictly a
+    /// subset of `self`; e.g. when removing a known sub-distribution to compute "what's left".
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lossless_transform_utils::histogram::Histogram32;
+    ///
+    /// let mut histogram = Histogram32::from_bytes(&[1, 2]);
+    /// histogram.saturating_sub(&Histogram32::from_bytes(&[2, 2, 2]));
+    /// assert_eq!(histogram.counter[1], 1);
+    /// assert_eq!(histogram.counter[2], 0);
+    /// ```
+    pub fn saturating_sub(&mut self, other: &Histogram32) {
+        for (count, &other) in self.counter.iter_mut().zip(other.counter.iter()) {
+            *count = count.saturating_sub(other);
+        }
+    }
+
+    /// Adds a flat slice of concatenated 256 element count arrays into `self`.
+    ///
+    /// This is the natural consumer of partial histograms produced outside of Rust, such as
+    /// from a GPU kernel or over FFI, which come as a single `[u32]` buffer. Each 256 element
+    /// chunk is treated as one partial histogram, and merged as if by [`Histogram32::merge`].
+    ///
+    /// # Panics
+    ///
+    /// If `flat.len()` is not a multiple of 256.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lossless_transform_utils::histogram::Histogram32;
+    ///
+    /// let mut flat = [0u32; 512];
+    /// flat[1] = 2; // first partial
+    /// flat[256 + 1] = 3; // second partial
+    ///
+    /// let mut histogram = Histogram32::default();
+    /// histogram.merge_from_flat(&flat);
+    /// assert_eq!(histogram.counter[1], 5);
+    /// ```
+    pub fn merge_from_flat(&mut self, flat: &[u32]) {
+        assert!(
+            flat.len().is_multiple_of(256),
+            "flat length ({}) must be a multiple of 256",
+            flat.len()
+        );
+
+        for chunk in flat.chunks_exact(256) {
+            for (count, &other) in self.counter.iter_mut().zip(chunk.iter()) {
+                *count = count.wrapping_add(other);
+            }
+        }
+    }
+
+    /// Returns a copy of the histogram with its counts proportionally rescaled, so that they
+    /// sum to (approximately) `target_total`.
+    ///
+    /// This allows comparing, or subtracting, histograms of inputs with different lengths while
+    /// staying in integers. Each count is rounded to the nearest integer, but byte values which
+    /// occur in `self` are kept at a count of at least 1; so the support of the distribution is
+    /// preserved. Both of these mean the total of the result may differ slightly from
+    /// `target_total`. Counts which don't fit in a [`u32`] saturate.
+    ///
+    /// An empty histogram stays empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lossless_transform_utils::histogram::Histogram32;
+    ///
+    /// let histogram = Histogram32::from_bytes(&[1, 1, 1, 2]);
+    /// let scaled = histogram.scale_to_total(400);
+    /// assert_eq!(scaled.counter[1], 300);
+    /// assert_eq!(scaled.counter[2], 100);
+    /// ```
+    pub fn scale_to_total(&self, target_total: u64) -> Histogram32 {
+        let mut scaled = Histogram32::default();
+        let total = self.total() as u128;
+        if total == 0 {
+            return scaled;
+        }
+
+        for (scaled, &count) in scaled.counter.iter_mut().zip(self.counter.iter()) {
+            if count == 0 {
+                continue;
+            }
+
+            let rescaled = (count as u128 * target_total as u128 + total / 2) / total;
+            *scaled = rescaled.clamp(1, u32::MAX as u128) as u32;
+        }
+
+        scaled
+    }
+}
+
+/// Counts the bytes of a slice into the existing counts of a histogram.
+///
+/// This is shorthand for calling [`histogram32_from_bytes`] on an existing histogram, which
+/// reads naturally in aggregation loops.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::histogram::Histogram32;
+///
+/// let first = [1, 2, 3];
+/// let second = [3, 4, 5];
+///
+/// let mut histogram = Histogram32::default();
+/// histogram += &first[..];
+/// histogram += &second[..];
+/// assert_eq!(histogram.counter, Histogram32::from_bytes(&[1, 2, 3, 3, 4, 5]).counter);
+/// ```
+impl<'a> AddAssign<&'a [u8]> for Histogram32 {
+    fn add_assign(&mut self, bytes: &'a [u8]) {
+        histogram32_from_bytes(bytes, self);
+    }
+}
+
+/// Merges the counts of `b` into `a`, returning the result; [`Histogram32::merge`] as a pure
+/// function, for use as the combine step of a parallel reduction.
+///
+/// The fold is associative and commutative (each count is a sum modulo 2^32), so partial
+/// histograms can be combined in any order and grouping, e.g. with `rayon`:
+/// `.reduce(Histogram32::default, |a, b| fold_histograms(a, &b))`.
+///
+/// # Overflow
+///
+/// Counts wrap on overflow, like [`Histogram32::merge`]; this can only happen once more than
+/// 4GiB of data has been counted in total. Wrapping keeps the fold associative, so even then
+/// the result is the same for every order (the true counts modulo 2^32).
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::histogram::{fold_histograms, Histogram32};
+///
+/// let partials = [b"hello", b"world"].map(|x| Histogram32::from_bytes(x));
+/// let total = partials.iter().fold(Histogram32::default(), fold_histograms);
+/// assert_eq!(total.counter, Histogram32::from_bytes(b"helloworld").counter);
+/// ```
+pub fn fold_histograms(mut a: Histogram32, b: &Histogram32) -> Histogram32 {
+    a.merge(b);
+    a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn merge_from_flat_equals_separate_merges() {
+        let partials = [
+            Histogram32::from_bytes(b"hello world"),
+            Histogram32::from_bytes(&[0, 0, 255, 128]),
+            Histogram32::from_bytes(b"lossless transform"),
+        ];
+        let flat: Vec<u32> = partials.iter().flat_map(|x| x.counter).collect();
+
+        let mut merged = Histogram32::from_bytes(b"base");
+        let mut expected = merged;
+        merged.merge_from_flat(&flat);
+        for partial in &partials {
+            expected.merge(partial);
+        }
+
+        assert_eq!(merged.counter, expected.counter);
+    }
+
+    #[test]
+    fn add_assign_accumulates_large_slices() {
+        let data: Vec<u8> = (0..10_000).map(|x| (x % 251) as u8).collect();
+        let (first, second) = data.split_at(4321);
+
+        let mut histogram = Histogram32::default();
+        histogram += first;
+        histogram += second;
+        assert_eq!(histogram.counter, Histogram32::from_bytes(&data).counter);
+    }
+
+    #[test]
+    fn subtract_undoes_merge() {
+        let original = Histogram32::from_bytes(b"hello world");
+        let other = Histogram32::from_bytes(b"other");
+
+        let mut histogram = original;
+        histogram.merge(&other);
+        histogram.subtract(&other);
+        assert_eq!(histogram.counter, original.counter);
+    }
+
+    #[test]
+    fn saturating_sub_clamps_at_zero() {
+        let mut histogram = Histogram32::from_bytes(&[5, 5, 6, 6, 6]);
+        let other = Histogram32::from_bytes(&[5, 5, 5, 5, 6]);
+        histogram.saturating_sub(&other);
+
+        assert_eq!(histogram.counter[5], 0);
+        assert_eq!(histogram.counter[6], 2);
+        assert_eq!(histogram.total(), 2);
+    }
+
+    #[test]
+    fn scale_to_total_makes_proportional_histograms_nearly_equal() {
+        let small: Vec<u8> = (0..3000).map(|x| (x % 7 * x % 23) as u8).collect();
+        let large = small.repeat(37);
+        let small = Histogram32::from_bytes(&small);
+        let large = Histogram32::from_bytes(&large);
+
+        let scaled = small.scale_to_total(large.total());
+        for (&scaled, &large) in scaled.counter.iter().zip(large.counter.iter()) {
+            assert!(scaled.abs_diff(large) <= 1, "{scaled} vs {large}");
+        }
+
+        assert_eq!(large.scale_to_total(small.total()).counter, small.counter);
+    }
+
+    #[test]
+    fn scale_to_total_keeps_rare_symbols() {
+        let mut data = [0u8; 10_000];
+        data[0] = 1;
+        let scaled = Histogram32::from_bytes(&data).scale_to_total(100);
+
+        assert_eq!(scaled.counter[0], 100);
+        assert_eq!(scaled.counter[1], 1);
+        assert_eq!(Histogram32::default().scale_to_total(100).total(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn merge_from_flat_rejects_partial_chunks() {
+        Histogram32::default().merge_from_flat(&[0; 300]);
+    }
+
+    #[test]
+    fn fold_is_order_independent() {
+        let partials = [
+            Histogram32::from_bytes(b"hello world"),
+            Histogram32::from_bytes(&[0, 0, 255, 128]),
+            Histogram32::from_bytes(b"lossless transform"),
+            Histogram32::from_bytes(&[u8::MAX; 1000]),
+        ];
+
+        let forward = partials
+            .iter()
+            .fold(Histogram32::default(), fold_histograms);
+        let backward = partials
+            .iter()
+            .rev()
+            .fold(Histogram32::default(), fold_histograms);
+        // (0 + 1) + (2 + 3), as a tree reduction would.
+        let tree = fold_histograms(
+            fold_histograms(partials[0], &partials[1]),
+            &fold_histograms(partials[2], &partials[3]),
+        );
+
+        assert_eq!(forward.counter, backward.counter);
+        assert_eq!(forward.counter, tree.counter);
+        assert_eq!(forward.total(), 11 + 4 + 18 + 1000);
+    }
+
+    #[test]
+    fn fold_wraps_on_overflow() {
+        let mut near_full = Histogram32::default();
+        near_full.counter[7] = u32::MAX;
+        let one = Histogram32::from_bytes(&[7, 7]);
+
+        assert_eq!(fold_histograms(near_full, &one).counter[7], 1);
+        assert_eq!(fold_histograms(one, &near_full).counter[7], 1);
+    }
+}
@@ -9,6 +9,8 @@ const SLICE_SIZE_U32S: usize = 256;
 /// Based on `histo_asm_scalar8_var5_core` by fabian 'ryg' giesen
 /// <https://gist.github.com/rygorous/a86a5cf348922cdea357c928e32fc7e0>
 ///
+/// This is [`histogram_nonaliased_core`] with the default of 4 slices.
+///
 /// # Safety
 ///
 /// This function is safe with any input.
@@ -22,8 +24,41 @@ const SLICE_SIZE_U32S: usize = 256;
 /// The reason may be something related to <https://www.agner.org/forum/viewtopic.php?t=41> .
 /// I did check the assembly, it's comparable (near identical) to ryg's original.
 pub fn histogram_nonaliased_withruns_core(data: &[u8], histogram_result: &mut Histogram32) {
-    // 1K on stack, should be good.
-    let mut histogram = [Histogram32::default(); NUM_SLICES];
+    histogram_nonaliased_core::<NUM_SLICES>(data, histogram_result)
+}
+
+/// [`histogram_nonaliased_core`] with 8 slices; every byte of each 8 byte read gets its own
+/// slice, so no two increments from the same read can alias.
+///
+/// Uses 8KiB of stack for the partial histograms, and merges 8 of them at the end.
+pub fn histogram_nonaliased_8slice(data: &[u8], histogram_result: &mut Histogram32) {
+    histogram_nonaliased_core::<8>(data, histogram_result)
+}
+
+/// Run-aware histogram, which spreads its increments over `SLICES` partial histograms, and sums
+/// them at the end.
+///
+/// Byte `n` of each 8 byte read goes into slice `n % SLICES`; so consecutive bytes with the same
+/// value increment different counters, rather than stalling on a store to load dependency.
+///
+/// # Remarks
+///
+/// The best slice count is hardware dependent. More slices mean less aliasing, but more stack
+/// and a larger merge at the end; e.g. 4 slices measured slower than the `batched`
+/// implementation on an AMD 5900X, while 8 slices has been reported faster on Skylake-X.
+/// Benchmark on the target machine (`benches/histogram`) before picking one.
+///
+/// # Panics
+///
+/// At compile time, if `SLICES` is 0.
+pub fn histogram_nonaliased_core<const SLICES: usize>(
+    data: &[u8],
+    histogram_result: &mut Histogram32,
+) {
+    const { assert!(SLICES > 0, "SLICES must be at least 1") };
+
+    // 1K per slice on stack, should be good.
+    let mut histogram = [Histogram32::default(); SLICES];
 
     unsafe {
         let mut ptr = data.as_ptr();
@@ -51,12 +86,12 @@ pub fn histogram_nonaliased_withruns_core(data: &[u8], histogram_result: &mut Hi
                         *current_ptr.add((current & 0xFF) as usize) += 16;
                     } else {
                         // Same 8 bytes twice - sum with INC2
-                        sum8(current_ptr, current, 2);
+                        sum8::<SLICES>(current_ptr, current, 2);
                     }
                 } else {
                     // Process both 8-byte chunks with INC1
-                    sum8(current_ptr, current, 1);
-                    sum8(current_ptr, next, 1);
+                    sum8::<SLICES>(current_ptr, current, 1);
+                    sum8::<SLICES>(current_ptr, next, 1);
                 }
 
                 current = ((ptr.add(16)) as *const u64).read_unaligned();
@@ -73,7 +108,7 @@ pub fn histogram_nonaliased_withruns_core(data: &[u8], histogram_result: &mut Hi
         // Sum up all bytes
         // Vectorization-friendly summation, LLVM is good at vectorizing this, so there's no need
         // to write this by hand.
-        if NUM_SLICES <= 1 {
+        if SLICES <= 1 {
             // Copy bytes.
             *histogram_result = histogram[0]
         } else {
@@ -85,7 +120,7 @@ pub fn histogram_nonaliased_withruns_core(data: &[u8], histogram_result: &mut Hi
 
                 // Changing to suggested code breaks.
                 #[allow(clippy::needless_range_loop)]
-                for slice in 0..NUM_SLICES {
+                for slice in 0..SLICES {
                     sum0 += histogram[slice].inner.counter[x];
                     sum1 += histogram[slice].inner.counter[x + 1];
                     sum2 += histogram[slice].inner.counter[x + 2];
@@ -102,10 +137,10 @@ pub fn histogram_nonaliased_withruns_core(data: &[u8], histogram_result: &mut Hi
 }
 
 #[inline(always)]
-unsafe fn sum8(current_ptr: *mut u32, mut value: u64, increment: u32) {
+unsafe fn sum8<const SLICES: usize>(current_ptr: *mut u32, mut value: u64, increment: u32) {
     for index in 0..8 {
         let byte = (value & 0xFF) as usize;
-        let slice_offset = (index % NUM_SLICES) * SLICE_SIZE_U32S;
+        let slice_offset = (index % SLICES) * SLICE_SIZE_U32S;
         let write_ptr = current_ptr.add(slice_offset + byte);
         let current = (write_ptr as *const u32).read_unaligned();
         (write_ptr).write_unaligned(current + increment);
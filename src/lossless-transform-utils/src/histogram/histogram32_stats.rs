@@ -0,0 +1,637 @@
+//! Queries and reductions over the distribution stored in a [`Histogram32`].
+//!
+//! These operate on an already computed histogram, so they are independent of the input size
+//! and only ever touch the 256 counters.
+
+use super::{most_common_of, Histogram32, HistogramError};
+use crate::entropy::{
+    code_length_of_histogram32_no_size, shannon_entropy_of_histogram32,
+    shannon_entropy_of_histogram32_f32,
+};
+use crate::math::sqrt;
+
+impl Histogram32 {
+    /// Returns the sum of all counts, i.e. the number of bytes counted into the histogram.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lossless_transform_utils::histogram::Histogram32;
+    ///
+    /// let histogram = Histogram32::from_bytes(&[1, 2, 3, 1]);
+    /// assert_eq!(histogram.total(), 4);
+    /// ```
+    pub fn total(&self) -> u64 {
+        self.counter.iter().map(|&x| x as u64).sum()
+    }
+
+    /// Returns the sum of all counts as a [`u32`], wrapping on overflow.
+    ///
+    /// For the common case where the input is known to be smaller than 4GiB; avoids summing
+    /// into a [`u64`] and casting back. Use [`Histogram32::checked_total_u32`] if the input
+    /// size is not known to fit.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lossless_transform_utils::histogram::Histogram32;
+    ///
+    /// let histogram = Histogram32::from_bytes(&[1, 2, 3, 1]);
+    /// assert_eq!(histogram.total_u32(), 4);
+    /// ```
+    pub fn total_u32(&self) -> u32 {
+        self.counter
+            .iter()
+            .fold(0_u32, |total, &count| total.wrapping_add(count))
+    }
+
+    /// Returns the sum of all counts as a [`u32`], or [`None`] if it overflows [`u32::MAX`].
+    pub fn checked_total_u32(&self) -> Option<u32> {
+        self.counter
+            .iter()
+            .try_fold(0_u32, |total, &count| total.checked_add(count))
+    }
+
+    /// Returns the sum of all counts, or [`HistogramError::Empty`] if there are none.
+    ///
+    /// Unlike [`Histogram32::total`], this keeps 'no data' distinct from valid data. An empty
+    /// histogram and one filled with a single repeated byte both have an entropy of 0.0, but
+    /// only the latter describes actual input; callers which must not treat missing data as
+    /// perfectly compressible should check this first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`HistogramError::Empty`] if every count is zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lossless_transform_utils::histogram::{Histogram32, HistogramError};
+    ///
+    /// assert_eq!(Histogram32::from_bytes(&[7, 7, 7]).total_checked(), Ok(3));
+    /// assert_eq!(
+    ///     Histogram32::default().total_checked(),
+    ///     Err(HistogramError::Empty)
+    /// );
+    /// ```
+    pub fn total_checked(&self) -> Result<u64, HistogramError> {
+        match self.total() {
+            0 => Err(HistogramError::Empty),
+            total => Ok(total),
+        }
+    }
+
+    /// Returns the highest count of any single byte value; 0 for an empty histogram.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lossless_transform_utils::histogram::Histogram32;
+    ///
+    /// let histogram = Histogram32::from_bytes(&[1, 2, 2, 3, 2]);
+    /// assert_eq!(histogram.mode_count(), 3);
+    /// ```
+    pub fn mode_count(&self) -> u32 {
+        self.counter.iter().copied().max().unwrap_or(0)
+    }
+
+    /// Returns the fraction of all bytes taken up by the most common byte value; i.e.
+    /// [`Histogram32::mode_count`] divided by [`Histogram32::total`].
+    ///
+    /// A cheap check for whether data is essentially a single repeated byte; a value near 1.0
+    /// means the data will compress extremely well with almost anything.
+    ///
+    /// # Returns
+    ///
+    /// A value in `1.0 / 256.0..=1.0`; or 0.0 for an empty histogram.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lossless_transform_utils::histogram::Histogram32;
+    ///
+    /// let histogram = Histogram32::from_bytes(&[0, 0, 0, 1]);
+    /// assert_eq!(histogram.dominant_fraction(), 0.75);
+    /// ```
+    pub fn dominant_fraction(&self) -> f64 {
+        let total = self.total();
+        if total == 0 {
+            return 0.0;
+        }
+
+        self.mode_count() as f64 / total as f64
+    }
+
+    /// Returns all 256 byte values ordered from most to least common.
+    ///
+    /// Ties are broken in favour of the lowest byte value (the module's tie-breaking rule), so the
+    /// order is fully deterministic.
+    pub(crate) fn symbols_by_count_desc(&self) -> [u8; 256] {
+        let mut symbols: [u8; 256] = core::array::from_fn(|x| x as u8);
+        symbols.sort_unstable_by(|&a, &b| {
+            self.counter[b as usize]
+                .cmp(&self.counter[a as usize])
+                .then(a.cmp(&b))
+        });
+        symbols
+    }
+
+    /// Zeroes the counts of all but the `k` most common byte values.
+    ///
+    /// This is useful for building reduced-alphabet transforms, where the `k` most common
+    /// symbols are kept and everything else is treated as an escape; the entropy of the reduced
+    /// distribution can then be computed directly from the mutated histogram.
+    ///
+    /// Ties are broken in favour of the lowest byte value. Passing a `k` of 256 or more
+    /// leaves the histogram unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lossless_transform_utils::histogram::Histogram32;
+    ///
+    /// let mut histogram = Histogram32::from_bytes(&[1, 1, 1, 2, 2, 3]);
+    /// histogram.retain_top_k(2);
+    ///
+    /// assert_eq!(histogram.counter[1], 3);
+    /// assert_eq!(histogram.counter[2], 2);
+    /// assert_eq!(histogram.counter[3], 0);
+    /// ```
+    pub fn retain_top_k(&mut self, k: usize) {
+        if k >= 256 {
+            return;
+        }
+
+        let symbols = self.symbols_by_count_desc();
+        for &symbol in &symbols[k..] {
+            self.counter[symbol as usize] = 0;
+        }
+    }
+
+    /// Returns the `N` most common byte values and their counts, from most to least common.
+    ///
+    /// Allocation free alternative for building small rank tables on the stack. Ties are broken
+    /// in favour of the lowest byte value. If fewer than `N` byte values occur in the data, the
+    /// remaining entries are padded with `(0, 0)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lossless_transform_utils::histogram::Histogram32;
+    ///
+    /// let histogram = Histogram32::from_bytes(&[7, 7, 7, 3, 3, 9]);
+    /// assert_eq!(histogram.most_common_n::<2>(), [(7, 3), (3, 2)]);
+    /// assert_eq!(histogram.most_common_n::<4>(), [(7, 3), (3, 2), (9, 1), (0, 0)]);
+    /// ```
+    pub fn most_common_n<const N: usize>(&self) -> [(u8, u32); N] {
+        let symbols = self.symbols_by_count_desc();
+        core::array::from_fn(|rank| match symbols.get(rank) {
+            Some(&symbol) if self.counter[symbol as usize] != 0 => {
+                (symbol, self.counter[symbol as usize])
+            }
+            _ => (0, 0),
+        })
+    }
+
+    /// Returns the most common byte value within `lo..=hi`, and its count.
+    ///
+    /// For analyzing a sub-alphabet, e.g. which digit or printable ASCII character dominates;
+    /// counts outside the range are ignored, however large. Ties are broken in favour of the
+    /// lowest byte value.
+    ///
+    /// # Returns
+    ///
+    /// [`None`] if no byte value in the range occurs, or the range is empty (`lo > hi`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lossless_transform_utils::histogram::Histogram32;
+    ///
+    /// let histogram = Histogram32::from_bytes(b"a1b22cccc");
+    /// assert_eq!(histogram.most_common_in_range(b'0', b'9'), Some((b'2', 2)));
+    /// assert_eq!(histogram.most_common_in_range(b'A', b'Z'), None);
+    /// ```
+    pub fn most_common_in_range(&self, lo: u8, hi: u8) -> Option<(u8, u32)> {
+        most_common_of((lo..=hi).map(|byte| (byte, self.counter[byte as usize])))
+    }
+
+    /// Returns the byte value at which the cumulative distribution, in ascending byte value
+    /// order, first reaches the fraction `p` of the total.
+    ///
+    /// Useful for detecting whether data concentrates in low or high byte values; e.g. a low
+    /// result for `p = 0.9` means 90% of the data is made of small byte values.
+    ///
+    /// Only byte values which occur in the data are returned, so `p = 0.0` yields the smallest
+    /// byte present, and `p = 1.0` the largest one.
+    ///
+    /// # Returns
+    ///
+    /// [`None`] if the histogram is empty, or `p` is not in the range `0.0..=1.0`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lossless_transform_utils::histogram::Histogram32;
+    ///
+    /// let histogram = Histogram32::from_bytes(&[0, 0, 0, 1, 200]);
+    /// assert_eq!(histogram.cumulative_byte_at_percentile(0.5), Some(0));
+    /// assert_eq!(histogram.cumulative_byte_at_percentile(0.8), Some(1));
+    /// assert_eq!(histogram.cumulative_byte_at_percentile(1.0), Some(200));
+    /// ```
+    pub fn cumulative_byte_at_percentile(&self, p: f64) -> Option<u8> {
+        if !(0.0..=1.0).contains(&p) {
+            return None;
+        }
+
+        let total = self.total();
+        let target = p * total as f64;
+        let mut cumulative = 0_u64;
+        for (byte, &count) in self.counter.iter().enumerate() {
+            if count == 0 {
+                continue;
+            }
+            cumulative += count as u64;
+            if cumulative as f64 >= target {
+                return Some(byte as u8);
+            }
+        }
+
+        None
+    }
+
+    /// Lists the byte values whose counts differ between `self` and `other`.
+    ///
+    /// Yields `(byte, self_count - other_count)` for every byte where the counts differ, in
+    /// ascending byte order. Handy for debugging transforms, by comparing the histogram from
+    /// before and after the transform.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lossless_transform_utils::histogram::Histogram32;
+    ///
+    /// let before = Histogram32::from_bytes(&[1, 1, 2]);
+    /// let after = Histogram32::from_bytes(&[1, 2, 2, 2]);
+    ///
+    /// let mut diff = before.diff_symbols(&after);
+    /// assert_eq!(diff.next(), Some((1, 1)));
+    /// assert_eq!(diff.next(), Some((2, -2)));
+    /// assert_eq!(diff.next(), None);
+    /// ```
+    pub fn diff_symbols<'a>(
+        &'a self,
+        other: &'a Histogram32,
+    ) -> impl Iterator<Item = (u8, i64)> + 'a {
+        self.counter
+            .iter()
+            .zip(other.counter.iter())
+            .enumerate()
+            .filter(|(_, (a, b))| a != b)
+            .map(|(byte, (&a, &b))| (byte as u8, a as i64 - b as i64))
+    }
+
+    /// Lists the byte values which are significantly more common in `self` than in `baseline`.
+    ///
+    /// Useful for anomaly detection: keep a histogram of typical data as the baseline, and check
+    /// new samples against it.
+    ///
+    /// # Model
+    ///
+    /// This is a heuristic, not a rigorous test. Each byte of `self` is treated as an
+    /// independent draw from the baseline distribution; so with `n` bytes in `self` and a
+    /// baseline probability `p`, the observed probability of the byte has a standard deviation of
+    /// about `sqrt(p * (1 - p) / n)`. A byte is yielded if its observed probability exceeds `p`
+    /// by more than `z_threshold` of these standard deviations (e.g. 3.0 for a ~0.1% false
+    /// positive rate per byte value, if the model held).
+    ///
+    /// Real data is rarely independent (runs, repeated structures), so expect more outliers than
+    /// the model predicts; tune `z_threshold` on your data. Byte values absent from the baseline
+    /// are yielded whenever they occur. Nothing is yielded if either histogram is empty.
+    ///
+    /// # Returns
+    ///
+    /// The outlier byte values, in ascending order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lossless_transform_utils::histogram::Histogram32;
+    ///
+    /// let baseline = Histogram32::from_bytes(&[1, 2, 3, 4].repeat(100));
+    /// let mut sample = [1, 2, 3, 4].repeat(25);
+    /// sample.extend_from_slice(&[4; 50]);
+    /// let sample = Histogram32::from_bytes(&sample);
+    ///
+    /// assert!(sample.outlier_symbols(&baseline, 3.0).eq([4]));
+    /// ```
+    pub fn outlier_symbols<'a>(
+        &'a self,
+        baseline: &'a Histogram32,
+        z_threshold: f64,
+    ) -> impl Iterator<Item = u8> + 'a {
+        let total = self.total() as f64;
+        let baseline_total = baseline.total() as f64;
+        let is_empty = total == 0.0 || baseline_total == 0.0;
+
+        self.counter
+            .iter()
+            .zip(baseline.counter.iter())
+            .enumerate()
+            .filter(move |&(_, (&count, &baseline_count))| {
+                if is_empty || count == 0 {
+                    return false;
+                }
+
+                let observed = count as f64 / total;
+                let expected = baseline_count as f64 / baseline_total;
+                let deviation = sqrt(expected * (1.0 - expected) / total);
+                observed - expected > z_threshold * deviation
+            })
+            .map(|(byte, _)| byte as u8)
+    }
+
+    /// Returns the Shannon entropy of the histogram, in bits per byte.
+    ///
+    /// Shortcut for [`code_length_of_histogram32_no_size`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lossless_transform_utils::histogram::Histogram32;
+    ///
+    /// let histogram = Histogram32::from_bytes(&[0, 1, 2, 3]);
+    /// assert_eq!(histogram.entropy(), 2.0);
+    /// ```
+    pub fn entropy(&self) -> f64 {
+        code_length_of_histogram32_no_size(self)
+    }
+
+    /// Returns the Shannon entropy of the histogram, in bits per byte, as if the byte values
+    /// in `exclude` were never counted.
+    ///
+    /// For formats which transmit some symbols out of band (e.g. an escape byte encoded
+    /// elsewhere); those cost nothing to entropy code, so both their counts and their share of
+    /// the total are removed. Duplicates in `exclude` are ignored.
+    ///
+    /// # Returns
+    ///
+    /// The entropy of the remaining bytes; 0.0 if every counted byte value is excluded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lossless_transform_utils::histogram::Histogram32;
+    ///
+    /// let histogram = Histogram32::from_bytes(&[0, 0, 0, 0, 1, 2]);
+    /// assert_eq!(histogram.entropy_excluding(&[0]), 1.0);
+    /// assert_eq!(histogram.entropy_excluding(&[0, 1, 2]), 0.0);
+    /// ```
+    pub fn entropy_excluding(&self, exclude: &[u8]) -> f64 {
+        let mut counter = self.counter;
+        for &byte in exclude {
+            counter[byte as usize] = 0;
+        }
+
+        let total = counter.iter().map(|&x| x as u64).sum();
+        shannon_entropy_of_histogram32(&counter, total)
+    }
+
+    /// Returns the Shannon entropy of the histogram, in bits per byte, computed in [`f32`].
+    ///
+    /// Faster, but less precise than [`Histogram32::entropy`]: accurate to ~1e-5 bits rather
+    /// than ~1e-12. See [`shannon_entropy_of_histogram32_f32`] for details.
+    pub fn entropy_f32(&self) -> f32 {
+        shannon_entropy_of_histogram32_f32(&self.counter, self.total())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::random_bytes;
+    use std::vec::Vec;
+
+    #[test]
+    fn dominant_fraction_of_single_byte_is_one() {
+        let histogram = Histogram32::from_bytes(&[0x55; 4096]);
+        assert_eq!(histogram.mode_count(), 4096);
+        assert_eq!(histogram.dominant_fraction(), 1.0);
+    }
+
+    #[test]
+    fn dominant_fraction_of_uniform_is_one_in_256() {
+        let data: Vec<u8> = (0..4096).map(|x| x as u8).collect();
+        let histogram = Histogram32::from_bytes(&data);
+        assert_eq!(histogram.mode_count(), 16);
+        assert!((histogram.dominant_fraction() - 1.0 / 256.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn dominant_fraction_of_empty_is_zero() {
+        assert_eq!(Histogram32::default().mode_count(), 0);
+        assert_eq!(Histogram32::default().dominant_fraction(), 0.0);
+    }
+
+    #[test]
+    fn outlier_symbols_flags_injected_byte() {
+        let data = random_bytes((1 << 16) + (1 << 12), 12345);
+        let baseline = &data[..1 << 16];
+        let mut sample = data[1 << 16..].to_vec();
+        // Byte 0x42 normally makes up 1/256 of the data; inject it as ~5% of the sample.
+        sample.extend_from_slice(&[0x42; 200]);
+
+        let baseline = Histogram32::from_bytes(baseline);
+        let sample = Histogram32::from_bytes(&sample);
+        let outliers: Vec<u8> = sample.outlier_symbols(&baseline, 5.0).collect();
+        assert_eq!(outliers, [0x42]);
+    }
+
+    #[test]
+    fn outlier_symbols_of_same_distribution_or_empty_is_empty() {
+        let histogram = Histogram32::from_bytes(b"the quick brown fox jumps over the lazy dog");
+        assert_eq!(histogram.outlier_symbols(&histogram, 3.0).count(), 0);
+        assert_eq!(
+            histogram
+                .outlier_symbols(&Histogram32::default(), 3.0)
+                .count(),
+            0
+        );
+
+        // Bytes never seen in the baseline are always outliers.
+        let unseen = Histogram32::from_bytes(b"the quick brown fox!");
+        assert!(unseen.outlier_symbols(&histogram, 3.0).eq(*b"!"));
+    }
+
+    #[test]
+    fn most_common_n_ranks_skewed_distribution() {
+        let mut data = Vec::new();
+        for (byte, count) in [(10u8, 5), (20, 40), (30, 1), (40, 20), (50, 10), (60, 10)] {
+            data.extend(core::iter::repeat_n(byte, count));
+        }
+        let histogram = Histogram32::from_bytes(&data);
+
+        assert_eq!(
+            histogram.most_common_n::<4>(),
+            [(20, 40), (40, 20), (50, 10), (60, 10)]
+        );
+    }
+
+    #[test]
+    fn most_common_n_pads_when_too_few_symbols() {
+        let histogram = Histogram32::from_bytes(&[5, 5, 6]);
+        assert_eq!(
+            histogram.most_common_n::<4>(),
+            [(5, 2), (6, 1), (0, 0), (0, 0)]
+        );
+        assert_eq!(Histogram32::default().most_common_n::<1>(), [(0, 0)]);
+
+        let padded = histogram.most_common_n::<300>();
+        assert!(padded[2..].iter().all(|&entry| entry == (0, 0)));
+    }
+
+    #[test]
+    fn most_common_in_range_ignores_bytes_outside_range() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&[b' '; 500]);
+        data.extend_from_slice(&[b'z'; 300]);
+        data.extend_from_slice(b"0123456789");
+        data.extend_from_slice(&[b'7'; 40]);
+        data.extend_from_slice(&[b'3'; 20]);
+
+        let histogram = Histogram32::from_bytes(&data);
+        assert_eq!(histogram.most_common_in_range(b'0', b'9'), Some((b'7', 41)));
+        assert_eq!(histogram.most_common_in_range(b'8', b'9'), Some((b'8', 1)));
+        assert_eq!(histogram.most_common_in_range(b'9', b'0'), None);
+    }
+
+    #[test]
+    fn retain_top_k_keeps_two_largest_counts() {
+        let histogram = Histogram32::from_bytes(&[5, 5, 5, 5, 9, 9, 9, 1, 1, 200]);
+        let mut reduced = histogram;
+        reduced.retain_top_k(2);
+
+        let nonzero = reduced.counter.iter().filter(|&&x| x > 0).count();
+        assert_eq!(nonzero, 2);
+        assert_eq!(reduced.counter[5], 4);
+        assert_eq!(reduced.counter[9], 3);
+    }
+
+    #[test]
+    fn retain_top_k_breaks_ties_by_lowest_byte() {
+        let mut histogram = Histogram32::from_bytes(&[7, 7, 3, 3, 5, 5]);
+        histogram.retain_top_k(2);
+
+        assert_eq!(histogram.counter[3], 2);
+        assert_eq!(histogram.counter[5], 2);
+        assert_eq!(histogram.counter[7], 0);
+    }
+
+    #[test]
+    fn retain_top_k_with_large_k_is_noop() {
+        let histogram = Histogram32::from_bytes(&[0, 1, 2, 3]);
+        let mut retained = histogram;
+        retained.retain_top_k(256);
+        assert_eq!(retained.counter, histogram.counter);
+    }
+
+    #[test]
+    fn total_u32_near_and_over_limit() {
+        let mut histogram = Histogram32::default();
+        histogram.counter[0] = u32::MAX - 10;
+        histogram.counter[255] = 10;
+        assert_eq!(histogram.total_u32(), u32::MAX);
+        assert_eq!(histogram.checked_total_u32(), Some(u32::MAX));
+
+        histogram.counter[128] = 1;
+        assert_eq!(histogram.total_u32(), 0);
+        assert_eq!(histogram.checked_total_u32(), None);
+        assert_eq!(histogram.total(), u32::MAX as u64 + 1);
+    }
+
+    #[test]
+    fn total_checked_distinguishes_empty_from_single_symbol() {
+        assert_eq!(
+            Histogram32::default().total_checked(),
+            Err(HistogramError::Empty)
+        );
+
+        let single = Histogram32::from_bytes(&[42; 100]);
+        assert_eq!(single.total_checked(), Ok(100));
+        assert_eq!(single.entropy(), 0.0);
+    }
+
+    #[test]
+    fn into_iterator_covers_all_symbols_in_order() {
+        let histogram = Histogram32::from_bytes(&[0, 0, 255]);
+        let pairs: Vec<(u8, u32)> = (&histogram).into_iter().collect();
+
+        assert_eq!(pairs.len(), 256);
+        assert!(pairs
+            .iter()
+            .enumerate()
+            .all(|(x, &(byte, _))| x == byte as usize));
+        assert_eq!(pairs[0], (0, 2));
+        assert_eq!(pairs[255], (255, 1));
+        assert_eq!((&histogram.inner).into_iter().count(), 256);
+    }
+
+    #[test]
+    fn cumulative_byte_at_percentile_on_low_skewed_distribution() {
+        // 90% of bytes are below 16, the rest are 250.
+        let data: Vec<u8> = (0..1000)
+            .map(|x| if x % 10 == 0 { 250 } else { (x % 16) as u8 })
+            .collect();
+        let histogram = Histogram32::from_bytes(&data);
+
+        assert_eq!(histogram.cumulative_byte_at_percentile(0.0), Some(0));
+        assert!(histogram.cumulative_byte_at_percentile(0.5).unwrap() < 16);
+        assert!(histogram.cumulative_byte_at_percentile(0.89).unwrap() < 16);
+        assert_eq!(histogram.cumulative_byte_at_percentile(0.95), Some(250));
+        assert_eq!(histogram.cumulative_byte_at_percentile(1.0), Some(250));
+    }
+
+    #[test]
+    fn cumulative_byte_at_percentile_rejects_invalid_input() {
+        let histogram = Histogram32::from_bytes(&[1, 2, 3]);
+        assert_eq!(histogram.cumulative_byte_at_percentile(-0.1), None);
+        assert_eq!(histogram.cumulative_byte_at_percentile(1.1), None);
+        assert_eq!(histogram.cumulative_byte_at_percentile(f64::NAN), None);
+        assert_eq!(
+            Histogram32::default().cumulative_byte_at_percentile(0.5),
+            None
+        );
+    }
+
+    #[test]
+    fn diff_symbols_yields_only_changed_bytes() {
+        let before = Histogram32::from_bytes(&[0, 1, 1, 2, 3, 3, 3]);
+        let after = Histogram32::from_bytes(&[0, 1, 2, 2, 2, 3, 3, 3]);
+
+        let diff: Vec<(u8, i64)> = before.diff_symbols(&after).collect();
+        assert_eq!(diff, [(1, 1), (2, -2)]);
+        assert_eq!(before.diff_symbols(&before).count(), 0);
+    }
+
+    #[test]
+    fn entropy_f32_is_close_to_entropy() {
+        let data: Vec<u8> = (0..10_000_u32).map(|x| (x * x % 253) as u8).collect();
+        let histogram = Histogram32::from_bytes(&data);
+        assert!((histogram.entropy_f32() as f64 - histogram.entropy()).abs() < 1e-4);
+        assert_eq!(Histogram32::default().entropy_f32(), 0.0);
+    }
+
+    #[test]
+    fn entropy_excluding_dominant_symbol() {
+        // 0 is 90% of the data; the rest is uniform over 4 values.
+        let mut data = std::vec![0u8; 900];
+        data.extend((0..100).map(|x| 1 + x % 4));
+        let histogram = Histogram32::from_bytes(&data);
+
+        assert!(histogram.entropy() < 1.0);
+        assert_eq!(histogram.entropy_excluding(&[0]), 2.0);
+        assert_eq!(histogram.entropy_excluding(&[]), histogram.entropy());
+        assert_eq!(histogram.entropy_excluding(&[0, 0]), 2.0);
+        assert_eq!(histogram.entropy_excluding(&[0, 1, 2, 3, 4]), 0.0);
+    }
+}
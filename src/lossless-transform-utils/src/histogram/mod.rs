@@ -8,9 +8,45 @@
 //!
 //! The histogram code in this module is built around calculating occurrences of bytes, the amount
 //! of times a byte has been met is stored.
+//!
+//! # Ties
+//!
+//! Every 'most common' query in this module breaks ties between equal counts in favour of the
+//! lowest byte value; so results are deterministic across versions and platforms, and safe to
+//! use in golden tests. This covers [`Histogram32::most_common_n`],
+//! [`Histogram32::retain_top_k`], [`Histogram32::most_common_in_range`] and
+//! [`most_likely_successor`].
 
+#[cfg(feature = "std")]
+pub mod bigram;
+#[cfg(feature = "std")]
+pub use bigram::*;
+#[cfg(feature = "std")]
+pub mod calibrate;
+#[cfg(feature = "std")]
+pub use calibrate::*;
+pub mod counting;
+pub use counting::*;
+pub mod distance;
+pub use distance::*;
+pub mod histogram16;
+pub use histogram16::*;
 pub mod histogram32;
 pub use histogram32::*;
+pub mod histogram32_builders;
+pub use histogram32_builders::*;
+mod histogram32_convert;
+mod histogram32_ops;
+pub use histogram32_ops::*;
+mod histogram32_stats;
+#[cfg(feature = "std")]
+pub mod parallel;
+#[cfg(feature = "std")]
+pub use parallel::*;
+#[cfg(feature = "test-helpers")]
+mod test_helpers;
+#[cfg(feature = "test-helpers")]
+pub use test_helpers::*;
 
 /// The implementation of a generic histogram, storing the for each byte using type `T`.
 /// `T` should be a type that can be incremented.
@@ -20,15 +56,75 @@ pub struct Histogram<T> {
     pub counter: [T; 256],
 }
 
+/// Errors returned by histogram conversions, and by queries which have no meaningful result for
+/// some histograms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistogramError {
+    /// The histogram has no counts, i.e. it was built from empty input.
+    Empty,
+    /// A histogram was built from a number of counts other than 256; holds the number given.
+    InvalidLength(usize),
+}
+
+impl core::fmt::Display for HistogramError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            HistogramError::Empty => f.write_str("histogram is empty"),
+            HistogramError::InvalidLength(len) => {
+                write!(f, "expected 256 histogram counts, got {len}")
+            }
+        }
+    }
+}
+
+/// Returns the `(byte, count)` pair with the highest non-zero count; [`None`] if every count is
+/// zero.
+///
+/// The shared implementation of the module's tie-breaking rule (see the module docs): `counts`
+/// must be in ascending byte order, and only a strictly higher count replaces the current best;
+/// so on ties, the lowest byte value wins.
+#[inline]
+pub(crate) fn most_common_of(counts: impl Iterator<Item = (u8, u32)>) -> Option<(u8, u32)> {
+    let mut best: Option<(u8, u32)> = None;
+    for (byte, count) in counts {
+        if count > best.map_or(0, |(_, best_count)| best_count) {
+            best = Some((byte, count));
+        }
+    }
+    best
+}
+
+/// Iterator over the `(byte, count)` pairs of a [`Histogram`], for all 256 byte values in order.
+pub type HistogramIter<'a, T> =
+    core::iter::Map<core::iter::Enumerate<core::slice::Iter<'a, T>>, fn((usize, &'a T)) -> (u8, T)>;
+
+impl<'a, T: Copy> IntoIterator for &'a Histogram<T> {
+    type Item = (u8, T);
+    type IntoIter = HistogramIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.counter
+            .iter()
+            .enumerate()
+            .map(|(byte, &count)| (byte as u8, count))
+    }
+}
+
 #[cfg(any(test, feature = "bench"))]
 pub mod histogram32_private;
 #[cfg(any(test, feature = "bench"))]
 pub use histogram32_private::*;
 
 /// Benchmark only re-exports.
+///
+/// Covers every histogram implementation in the crate: the reference one, the batched one
+/// (which picks its BMI1 or portable inner loop at runtime), and the non-aliased ones with 4 and
+/// 8 slices; the best slice count depends on the hardware. There are no SIMD (AVX2/SSE2)
+/// histogram implementations yet; they belong here, and in `benches/histogram`, once added.
 #[cfg(feature = "bench")]
 pub mod bench {
     use super::Histogram32;
+    pub use super::{histogram_nonaliased_8slice, histogram_nonaliased_withruns_core};
 
     pub fn histogram32_generic_batched_unroll_4_u32(bytes: &[u8], histogram: &mut Histogram32) {
         super::histogram32_generic_batched_unroll_4_u32(bytes, histogram)
@@ -38,3 +134,59 @@ pub mod bench {
         super::histogram32_reference(bytes, histogram)
     }
 }
+
+#[cfg(test)]
+mod tie_breaking_tests {
+    use super::*;
+
+    /// Bytes 5, 9 and 200 tie for the highest count; 1 and 7 tie for a lower one.
+    fn tied_histogram() -> Histogram32 {
+        let mut histogram = Histogram32::default();
+        for (byte, count) in [(200, 10), (9, 10), (5, 10), (7, 3), (1, 3)] {
+            histogram.counter[byte] = count;
+        }
+        histogram
+    }
+
+    #[test]
+    fn most_common_n_ranks_ties_by_lowest_byte() {
+        let histogram = tied_histogram();
+        assert_eq!(histogram.most_common_n::<1>(), [(5, 10)]);
+        assert_eq!(
+            histogram.most_common_n::<5>(),
+            [(5, 10), (9, 10), (200, 10), (1, 3), (7, 3)]
+        );
+    }
+
+    #[test]
+    fn retain_top_k_keeps_lowest_tied_bytes() {
+        let mut histogram = tied_histogram();
+        histogram.retain_top_k(4);
+        let kept: [bool; 256] = core::array::from_fn(|x| histogram.counter[x] != 0);
+        assert!(kept[5] && kept[9] && kept[200] && kept[1]);
+        assert!(!kept[7]);
+    }
+
+    #[test]
+    fn most_common_in_range_picks_lowest_tied_byte() {
+        let histogram = tied_histogram();
+        assert_eq!(histogram.most_common_in_range(0, 255), Some((5, 10)));
+        assert_eq!(histogram.most_common_in_range(6, 255), Some((9, 10)));
+        assert_eq!(histogram.most_common_in_range(0, 4), Some((1, 3)));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn most_likely_successor_picks_lowest_tied_byte() {
+        // 'a' is followed by 'z', 'c' and 'b' twice each.
+        let bigram = Bigram32::from_bytes(b"azacabazacab");
+        assert_eq!(most_likely_successor(&bigram, b'a'), Some((b'b', 2)));
+    }
+
+    #[test]
+    fn shared_helper_prefers_lowest_byte_and_skips_zero() {
+        let counts = [(3, 0), (4, 7), (8, 7), (9, 2)];
+        assert_eq!(most_common_of(counts.into_iter()), Some((4, 7)));
+        assert_eq!(most_common_of([(0, 0), (1, 0)].into_iter()), None);
+    }
+}
@@ -0,0 +1,130 @@
+//! Histogramming large inputs on multiple threads.
+
+use super::{histogram32_from_bytes, Histogram32};
+use std::thread;
+use std::vec::Vec;
+
+/// Smallest number of bytes each thread of [`histogram32_from_bytes_parallel`] is given.
+///
+/// Below this, spawning a thread costs more than histogramming the bytes on the current one.
+pub const MIN_PARALLEL_CHUNK_SIZE: usize = 256 * 1024;
+
+/// Calculates the histogram of `bytes` on multiple threads.
+///
+/// The input is split into one chunk per available CPU (but no chunk smaller than
+/// [`MIN_PARALLEL_CHUNK_SIZE`]); each chunk is histogrammed with [`histogram32_from_bytes`] into
+/// its own partial histogram, and the partials are then added onto `hist`. The result is
+/// identical to calling [`histogram32_from_bytes`] on the whole input.
+///
+/// This allocates the partial histograms on every call; for repeated calls, use
+/// [`histogram32_from_bytes_parallel_with_scratch`] to reuse them.
+///
+/// # Arguments
+///
+/// * `bytes` - The input data.
+/// * `hist` - The histogram to add the counts to.
+pub fn histogram32_from_bytes_parallel(bytes: &[u8], hist: &mut Histogram32) {
+    histogram32_from_bytes_parallel_with_scratch(bytes, hist, &mut Vec::new());
+}
+
+/// Same as [`histogram32_from_bytes_parallel`], but keeps the per chunk partial histograms in
+/// `scratch`, so repeated calls don't allocate.
+///
+/// # Scratch Sizing
+///
+/// `scratch` is cleared and resized to the number of chunks the input is split into; i.e.
+/// `min(available CPUs, bytes.len() / MIN_PARALLEL_CHUNK_SIZE)`, and at least 1. Its capacity is
+/// kept, so once it has held as many partials as the largest input needs, no further
+/// allocations are made. Each partial is 1KiB. Its contents after the call are unspecified.
+///
+/// # Arguments
+///
+/// * `bytes` - The input data.
+/// * `hist` - The histogram to add the counts to.
+/// * `scratch` - Storage for the partial histograms; may start empty.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::histogram::{
+///     histogram32_from_bytes_parallel_with_scratch, Histogram32,
+/// };
+///
+/// let mut scratch = Vec::new();
+/// for block in [vec![1u8; 1 << 20], vec![2u8; 1 << 20]] {
+///     let mut histogram = Histogram32::default();
+///     histogram32_from_bytes_parallel_with_scratch(&block, &mut histogram, &mut scratch);
+///     assert_eq!(histogram.counter[block[0] as usize], 1 << 20);
+/// }
+/// ```
+pub fn histogram32_from_bytes_parallel_with_scratch(
+    bytes: &[u8],
+    hist: &mut Histogram32,
+    scratch: &mut Vec<Histogram32>,
+) {
+    let threads = thread::available_parallelism().map_or(1, |x| x.get());
+    let num_chunks = (bytes.len() / MIN_PARALLEL_CHUNK_SIZE).clamp(1, threads);
+    scratch.clear();
+    scratch.resize(num_chunks, Histogram32::default());
+
+    if num_chunks == 1 {
+        histogram32_from_bytes(bytes, hist);
+        return;
+    }
+
+    let chunk_size = bytes.len().div_ceil(num_chunks);
+    thread::scope(|scope| {
+        let mut chunks = bytes.chunks(chunk_size).zip(scratch.iter_mut());
+        // The current thread takes the first chunk, rather than idling until the others finish.
+        let first = chunks.next();
+        for (chunk, partial) in chunks {
+            scope.spawn(move || histogram32_from_bytes(chunk, partial));
+        }
+        if let Some((chunk, partial)) = first {
+            histogram32_from_bytes(chunk, partial);
+        }
+    });
+
+    for partial in scratch.iter() {
+        hist.merge(partial);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::random_bytes;
+
+    #[test]
+    fn reused_scratch_matches_serial() {
+        let mut scratch = Vec::new();
+        let sizes = [
+            4 * MIN_PARALLEL_CHUNK_SIZE + 123,
+            1000,
+            0,
+            9 * MIN_PARALLEL_CHUNK_SIZE,
+        ];
+        for (seed, size) in sizes.into_iter().enumerate() {
+            let data = random_bytes(size, seed as u32);
+            let mut parallel = Histogram32::default();
+            histogram32_from_bytes_parallel_with_scratch(&data, &mut parallel, &mut scratch);
+            assert_eq!(
+                parallel.counter,
+                Histogram32::from_bytes(&data).counter,
+                "size {size}"
+            );
+            assert!(!scratch.is_empty());
+        }
+    }
+
+    #[test]
+    fn parallel_adds_onto_existing_counts() {
+        let data = random_bytes(3 * MIN_PARALLEL_CHUNK_SIZE, 42);
+        let mut histogram = Histogram32::from_bytes(&data);
+        histogram32_from_bytes_parallel(&data, &mut histogram);
+
+        let mut expected = Histogram32::from_bytes(&data);
+        expected.merge(&Histogram32::from_bytes(&data));
+        assert_eq!(histogram.counter, expected.counter);
+    }
+}
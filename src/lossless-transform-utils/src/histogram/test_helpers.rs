@@ -0,0 +1,179 @@
+//! Helpers for testing code which produces or consumes histograms, e.g. fuzz harnesses and
+//! custom histogram implementations in downstream crates.
+//!
+//! Only available with the `test-helpers` feature.
+
+use super::{histogram32_reference, Histogram32};
+use crate::match_estimator::hash_u32;
+use core::alloc::Layout;
+use safe_allocator_api::RawAlloc;
+
+/// Checks a candidate histogram implementation against the reference implementation, for
+/// every input size in `0..=max_size`.
+///
+/// This is the same differential test the crate runs against its own implementations
+/// (with `max_size` of 767), exposed so downstream crates can validate their own backends.
+/// The input for each size is the bytes `0, 1, 2, ..., 255, 0, 1, ...` truncated to that size.
+///
+/// # Arguments
+///
+/// * `f` - The implementation to test. Called with a zeroed [`Histogram32`] for each size.
+/// * `max_size` - The largest input size to test (inclusive).
+///
+/// # Panics
+///
+/// If the implementation's result differs from the reference for any size; the message
+/// includes the size which failed.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::histogram::{assert_histogram_matches_reference, histogram32_from_bytes};
+///
+/// assert_histogram_matches_reference(histogram32_from_bytes, 767);
+/// ```
+pub fn assert_histogram_matches_reference(f: fn(&[u8], &mut Histogram32), max_size: usize) {
+    // Every tested input is a prefix of the largest one, so it's generated once.
+    // Zeroed, as a slice over uninitialized memory is undefined behaviour.
+    let mut alloc = RawAlloc::new_zeroed(Layout::array::<u8>(max_size.max(1)).unwrap()).unwrap();
+    let data = alloc.as_mut_slice();
+    for (index, byte) in data.iter_mut().enumerate() {
+        *byte = index as u8;
+    }
+
+    for size in 0..=max_size {
+        let test_data = &data[..size];
+
+        let mut implementation_result = Histogram32::default();
+        let mut reference_result = Histogram32::default();
+        f(test_data, &mut implementation_result);
+        histogram32_reference(test_data, &mut reference_result);
+
+        assert!(
+            implementation_result.counter == reference_result.counter,
+            "Implementation failed for size {size}"
+        );
+    }
+}
+
+/// Generates a pseudo random permutation of all 256 byte values, reproducible from `seed`.
+///
+/// Useful for building varied, but reproducible byte remap tables in tests, without depending
+/// on a random number generator crate.
+///
+/// The output is always a valid permutation (bijection): every byte value appears exactly
+/// once, so `table[byte]` can be used as a lossless remap, and inverted. It's a Fisher-Yates
+/// shuffle of the identity table, driven by the golden ratio hash of the match estimator
+/// ([`hash_u32`]) over a counter. Not suitable for anything security related.
+///
+/// [`hash_u32`]: crate::match_estimator::hash_u32
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::histogram::deterministic_permutation;
+///
+/// let table = deterministic_permutation(42);
+/// assert_eq!(table, deterministic_permutation(42));
+///
+/// let mut inverse = [0u8; 256];
+/// for (byte, &mapped) in table.iter().enumerate() {
+///     inverse[mapped as usize] = byte as u8;
+/// }
+/// assert_eq!(inverse[table[7] as usize], 7);
+/// ```
+pub fn deterministic_permutation(seed: u64) -> [u8; 256] {
+    // Both halves of the seed pick the starting counter.
+    let mut counter = hash_u32((seed >> 32) as u32) ^ seed as u32;
+    let mut next = || {
+        counter = counter.wrapping_add(1);
+        // The multiply only carries upwards; fold the well mixed upper bits down and hash again.
+        let hash = hash_u32(counter);
+        hash_u32(hash ^ (hash >> 16))
+    };
+
+    let mut table: [u8; 256] = core::array::from_fn(|x| x as u8);
+    for index in (1..table.len()).rev() {
+        // Scales the hash to `0..=index`, using its upper bits.
+        let swap_with = ((next() as u64 * (index as u64 + 1)) >> 32) as usize;
+        table.swap(index, swap_with);
+    }
+
+    table
+}
+
+impl Histogram32 {
+    /// Checks whether this histogram holds exactly the byte counts of `bytes`.
+    ///
+    /// The counts are recomputed with the simple reference implementation, which is the same
+    /// differential check the crate's own tests use against the optimized implementations.
+    /// Meant for fuzz/property tests, i.e. `assert!(hist.verify_against(input))`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lossless_transform_utils::histogram::Histogram32;
+    ///
+    /// let input = b"fuzzer provided input";
+    /// let histogram = Histogram32::from_bytes(input);
+    /// assert!(histogram.verify_against(input));
+    /// ```
+    pub fn verify_against(&self, bytes: &[u8]) -> bool {
+        let mut reference = Histogram32::default();
+        histogram32_reference(bytes, &mut reference);
+        self.counter == reference.counter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn batched_path_matches_reference() {
+        assert_histogram_matches_reference(
+            crate::histogram::histogram32::histogram32_generic_batched_unroll_4_u32,
+            767,
+        );
+        assert_histogram_matches_reference(crate::histogram::histogram32_from_bytes, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Implementation failed for size 1")]
+    fn broken_implementation_is_rejected() {
+        // Drops the last byte of every input.
+        fn broken(bytes: &[u8], histogram: &mut Histogram32) {
+            let bytes = &bytes[..bytes.len().saturating_sub(1)];
+            crate::histogram::histogram32_from_bytes(bytes, histogram)
+        }
+        assert_histogram_matches_reference(broken, 16);
+    }
+
+    #[test]
+    fn verify_against_accepts_correct_and_rejects_corrupted() {
+        let input: Vec<u8> = (0..1000).map(|x| (x % 7) as u8).collect();
+        let mut histogram = Histogram32::from_bytes(&input);
+        assert!(histogram.verify_against(&input));
+
+        histogram.counter[3] += 1;
+        assert!(!histogram.verify_against(&input));
+        assert!(!Histogram32::default().verify_against(&input));
+    }
+
+    #[test]
+    fn deterministic_permutation_is_a_permutation() {
+        for seed in [0, 1, 42, u64::MAX] {
+            let table = deterministic_permutation(seed);
+            let mut seen = [false; 256];
+            for &byte in &table {
+                assert!(!seen[byte as usize], "seed {seed} repeats {byte}");
+                seen[byte as usize] = true;
+            }
+        }
+
+        let identity: [u8; 256] = core::array::from_fn(|x| x as u8);
+        assert_ne!(deterministic_permutation(0), identity);
+        assert_ne!(deterministic_permutation(0), deterministic_permutation(1));
+    }
+}
@@ -0,0 +1,129 @@
+//! Per-thread timing of the most recent top level histogram and match estimator calls.
+//!
+//! This is for diagnostics, e.g. profiling a pipeline without an external profiler; not for
+//! production use. Each instrumented call reads the clock twice, which is noticeable for
+//! small inputs.
+//!
+//! Only available with the `instrument` feature. When the feature is disabled, no timing code
+//! is compiled into the instrumented functions at all.
+//!
+//! # Example
+//!
+//! ```
+//! use lossless_transform_utils::histogram::Histogram32;
+//! use lossless_transform_utils::instrument::last_histogram_timing;
+//!
+//! let histogram = Histogram32::from_bytes(&[0u8; 4096]);
+//! let timing = last_histogram_timing().unwrap();
+//! assert_eq!(timing.bytes, 4096);
+//! println!("{} bytes/s", timing.bytes_per_second());
+//! ```
+
+use core::cell::Cell;
+use std::thread_local;
+use std::time::{Duration, Instant};
+
+/// Timing of a single instrumented call.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CallTiming {
+    /// Time spent in the call.
+    pub duration: Duration,
+    /// Number of input bytes processed by the call.
+    pub bytes: usize,
+}
+
+impl CallTiming {
+    /// Returns the throughput of the call, in bytes per second.
+    ///
+    /// Returns [`f64::INFINITY`] if the call was too fast for the clock to measure (a zero
+    /// duration), unless it processed no bytes; then the throughput is 0.
+    pub fn bytes_per_second(&self) -> f64 {
+        if self.bytes == 0 {
+            return 0.0;
+        }
+        if self.duration.is_zero() {
+            return f64::INFINITY;
+        }
+        self.bytes as f64 / self.duration.as_secs_f64()
+    }
+}
+
+thread_local! {
+    static LAST_HISTOGRAM: Cell<Option<CallTiming>> = const { Cell::new(None) };
+    static LAST_MATCH_ESTIMATOR: Cell<Option<CallTiming>> = const { Cell::new(None) };
+}
+
+/// Returns the timing of the most recent histogram computation on the current thread.
+///
+/// Returns [`None`] if no histogram has been computed on this thread yet.
+pub fn last_histogram_timing() -> Option<CallTiming> {
+    LAST_HISTOGRAM.with(Cell::get)
+}
+
+/// Returns the timing of the most recent match estimation on the current thread.
+///
+/// Returns [`None`] if no match estimation has been done on this thread yet.
+pub fn last_match_estimator_timing() -> Option<CallTiming> {
+    LAST_MATCH_ESTIMATOR.with(Cell::get)
+}
+
+#[inline]
+pub(crate) fn record_histogram(start: Instant, bytes: usize) {
+    LAST_HISTOGRAM.with(|x| x.set(Some(timing_since(start, bytes))));
+}
+
+#[inline]
+pub(crate) fn record_match_estimator(start: Instant, bytes: usize) {
+    LAST_MATCH_ESTIMATOR.with(|x| x.set(Some(timing_since(start, bytes))));
+}
+
+#[inline]
+fn timing_since(start: Instant, bytes: usize) -> CallTiming {
+    CallTiming {
+        duration: start.elapsed(),
+        bytes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::histogram::{histogram32_from_array, Histogram32};
+    use crate::match_estimator::estimate_num_lz_matches_fast;
+    use std::thread;
+
+    #[test]
+    fn calls_populate_timing() {
+        // Fresh thread, so other tests can't have populated the timings.
+        thread::spawn(|| {
+            assert_eq!(last_histogram_timing(), None);
+            assert_eq!(last_match_estimator_timing(), None);
+
+            Histogram32::from_bytes(&[1u8; 1000]);
+            estimate_num_lz_matches_fast(&[2u8; 2000]);
+
+            assert_eq!(last_histogram_timing().unwrap().bytes, 1000);
+            assert_eq!(last_match_estimator_timing().unwrap().bytes, 2000);
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn array_calls_populate_timing() {
+        thread::spawn(|| {
+            histogram32_from_array(&[1u8; 100], &mut Histogram32::default());
+            assert_eq!(last_histogram_timing().unwrap().bytes, 100);
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn bytes_per_second_handles_zero_duration() {
+        let timing = |bytes, duration| CallTiming { duration, bytes };
+        assert_eq!(timing(10, Duration::ZERO).bytes_per_second(), f64::INFINITY);
+        assert_eq!(timing(0, Duration::ZERO).bytes_per_second(), 0.0);
+        assert_eq!(timing(10, Duration::from_secs(2)).bytes_per_second(), 5.0);
+    }
+}
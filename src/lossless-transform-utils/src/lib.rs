@@ -1,6 +1,8 @@
 #![doc = include_str!(concat!("../", env!("CARGO_PKG_README")))]
 #![no_std]
 #![cfg_attr(feature = "nightly", feature(naked_functions))]
+// `safe-allocator-api` uses the `core` allocator API types on nightly; needed for `MatchEstimator`.
+#![cfg_attr(feature = "nightly", feature(allocator_api))]
 #![cfg_attr(
     all(
         any(target_arch = "x86", target_arch = "x86_64"),
@@ -27,6 +29,13 @@ pub mod exports;
 #[cfg(feature = "std")]
 extern crate std;
 
+pub mod backends;
 pub mod entropy;
+pub mod estimate;
 pub mod histogram;
+#[cfg(feature = "instrument")]
+pub mod instrument;
 pub mod match_estimator;
+mod math;
+#[cfg(test)]
+mod test_utils;
@@ -20,7 +20,8 @@ pub(crate) unsafe fn calculate_matches_avx2(
     const SHIFT_RIGHT: i32 = 32 - HASH_BITS as i32;
     let mut matches_accumulator = _mm256_setzero_si256();
 
-    // Process 8 positions at once using AVX2
+    // Process 32 positions (8 per vector) at once.
+    // The loads read 35 bytes, i.e. 3 bytes past the last position, hence the bound.
     while begin_ptr.add(35) <= end_ptr {
         // Load 32 bytes to process 8 positions with unaligned loads
         let bytes0 = _mm256_loadu_si256(begin_ptr as *const __m256i);
@@ -117,7 +118,8 @@ pub(crate) unsafe fn calculate_matches_avx2(
         hash_table[indices[30] as usize] = data[30];
         hash_table[indices[31] as usize] = data[31];
 
-        begin_ptr = begin_ptr.add(35);
+        // Positions 0..32 were processed; the bytes past that are part of the next positions.
+        begin_ptr = begin_ptr.add(32);
     }
 
     // Add matches from accumulator to total matches
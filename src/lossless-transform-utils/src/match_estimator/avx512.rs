@@ -4,10 +4,30 @@ use core::arch::x86::*;
 #[cfg(target_arch = "x86_64")]
 use core::arch::x86_64::*;
 
+/// Number of loop iterations after which the per-lane match accumulator is flushed into `matches`.
+///
+/// Each iteration adds at most 4 to a single 32-bit lane, so a lane would wrap after
+/// `u32::MAX / 4` iterations, i.e. roughly 32GiB of input. Flushing every 2^24 iterations
+/// (~512MiB of input) keeps every lane far away from that bound, while keeping the horizontal
+/// sum well out of the hot loop.
+const ACCUMULATOR_FLUSH_ITERATIONS: usize = 1 << 24;
+
+#[inline(always)]
+pub(crate) unsafe fn calculate_matches_avx512(
+    hash_table: &mut [u32; HASH_SIZE],
+    matches: &mut usize,
+    begin_ptr: *const u8,
+    end_ptr: *const u8,
+) {
+    calculate_matches_avx512_with_flush_interval::<ACCUMULATOR_FLUSH_ITERATIONS>(
+        hash_table, matches, begin_ptr, end_ptr,
+    );
+}
+
 #[target_feature(enable = "avx512f")]
 #[target_feature(enable = "avx512vl")]
 #[inline(never)]
-pub(crate) unsafe fn calculate_matches_avx512(
+unsafe fn calculate_matches_avx512_with_flush_interval<const FLUSH_ITERATIONS: usize>(
     hash_table: &mut [u32; HASH_SIZE],
     matches: &mut usize,
     mut begin_ptr: *const u8,
@@ -18,8 +38,10 @@ pub(crate) unsafe fn calculate_matches_avx512(
 
     const SHIFT_RIGHT: i32 = 32 - HASH_BITS as i32;
     let mut matches_accumulator = _mm256_setzero_si256();
+    let mut iterations_until_flush = FLUSH_ITERATIONS;
 
-    // Process 8 positions at once using AVX2
+    // Process 32 positions (8 per vector) at once.
+    // The loads read 35 bytes, i.e. 3 bytes past the last position, hence the bound.
     while begin_ptr.add(35) <= end_ptr {
         // Load 32 bytes to process 8 positions with unaligned loads
         let bytes0 = _mm256_loadu_si256(begin_ptr as *const __m256i);
@@ -72,19 +94,79 @@ pub(crate) unsafe fn calculate_matches_avx512(
         _mm256_i32scatter_epi32(hash_table.as_mut_ptr().cast(), idx2, d2, 4);
         _mm256_i32scatter_epi32(hash_table.as_mut_ptr().cast(), idx3, d3, 4);
 
-        begin_ptr = begin_ptr.add(35);
+        // Positions 0..32 were processed; the bytes past that are part of the next positions.
+        begin_ptr = begin_ptr.add(32);
+
+        // Move the lane counts into `matches` before any lane can wrap.
+        iterations_until_flush -= 1;
+        if iterations_until_flush == 0 {
+            flush_accumulator(&mut matches_accumulator, matches);
+            iterations_until_flush = FLUSH_ITERATIONS;
+        }
     }
 
     // Add matches from accumulator to total matches
-    let mut match_counts = [0u32; 32];
+    flush_accumulator(&mut matches_accumulator, matches);
+
+    // Handle remaining bytes with scalar code
+    calculate_matches_generic(hash_table, matches, begin_ptr, end_ptr);
+}
+
+/// Adds the per-lane match counts to `matches` and resets the accumulator.
+#[target_feature(enable = "avx512f")]
+#[target_feature(enable = "avx512vl")]
+#[inline]
+unsafe fn flush_accumulator(matches_accumulator: &mut __m256i, matches: &mut usize) {
+    let mut match_counts = [0u32; 8];
     _mm256_storeu_si256(
         match_counts.as_mut_ptr() as *mut __m256i,
-        matches_accumulator,
+        *matches_accumulator,
     );
     for m in match_counts {
         *matches += m as usize;
     }
+    *matches_accumulator = _mm256_setzero_si256();
+}
 
-    // Handle remaining bytes with scalar code
-    calculate_matches_generic(hash_table, matches, begin_ptr, end_ptr);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::is_x86_feature_detected;
+    use std::vec;
+
+    fn run<const FLUSH_ITERATIONS: usize>(bytes: &[u8]) -> usize {
+        let mut table = vec![0u32; HASH_SIZE];
+        let hash_table: &mut [u32; HASH_SIZE] = (&mut table[..]).try_into().unwrap();
+        let mut matches = 0;
+        unsafe {
+            let begin_ptr = bytes.as_ptr();
+            let end_ptr = begin_ptr.add(bytes.len().saturating_sub(7));
+            calculate_matches_avx512_with_flush_interval::<FLUSH_ITERATIONS>(
+                hash_table,
+                &mut matches,
+                begin_ptr,
+                end_ptr,
+            );
+        }
+        matches
+    }
+
+    #[test]
+    fn accumulator_flush_keeps_exact_count_on_repetitive_data() {
+        if !(is_x86_feature_detected!("avx512f") && is_x86_feature_detected!("avx512vl")) {
+            return;
+        }
+
+        // An all-zero buffer hits the zeroed table on every processed position, so every lane
+        // grows at the maximum rate of 4 per iteration. 8MiB is a CI-sized stand-in for the
+        // (~32GiB) input needed to wrap a lane; the small flush intervals exercise the flush path.
+        let data = vec![0u8; 8 * 1024 * 1024];
+
+        // Every position is checked; the scalar tail rounds up to a multiple of 4 positions.
+        let expected = (data.len() - 7).div_ceil(4) * 4;
+
+        assert_eq!(run::<1>(&data), expected);
+        assert_eq!(run::<1024>(&data), expected);
+        assert_eq!(run::<ACCUMULATOR_FLUSH_ITERATIONS>(&data), expected);
+    }
 }
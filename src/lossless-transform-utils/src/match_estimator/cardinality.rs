@@ -0,0 +1,105 @@
+//! Match estimation combined with distinct 3-gram counting, in a single pass.
+
+use super::{for_each_table_slot, HASH_SIZE, OCCUPIED};
+use crate::math::{ln, round};
+
+/// Estimates both the number of >=3 byte LZ matches, and the number of distinct 3 byte
+/// sequences (3-grams) in the input, in a single pass.
+///
+/// Both numbers come from the same hash table: a hash hit with identical data counts as a
+/// match, and a write into a previously empty slot counts towards the cardinality.
+///
+/// # Arguments
+///
+/// * `bytes` - The input data stream.
+///
+/// # Returns
+///
+/// A tuple of (estimated match count, estimated distinct 3-gram count).
+///
+/// # Remarks
+///
+/// Both numbers share the hash collision caveats of the table: distinct 3-grams hashing to the
+/// same slot evict each other, which hides some matches and merges some distinct 3-grams.
+/// The cardinality is corrected for this with 'linear counting' (`-m * ln(empty / m)`, where
+/// `m` is the number of slots); which is accurate until the 32768 slot table is nearly full,
+/// i.e. up to a few hundred thousand distinct 3-grams. Beyond that it saturates at ~340K.
+///
+/// Unlike [`estimate_num_lz_matches_fast`], every position is hashed in order, and the
+/// initially empty table does not produce false matches for zero bytes. So the match counts
+/// of the two functions are close, but not identical.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::match_estimator::estimate_match_and_cardinality;
+///
+/// let (matches, distinct) = estimate_match_and_cardinality(b"abcabcabcabc");
+/// assert_eq!(distinct, 3); // "abc", "bca", "cab"
+/// assert_eq!(matches, 6);
+/// ```
+///
+/// [`estimate_num_lz_matches_fast`]: super::estimate_num_lz_matches_fast
+pub fn estimate_match_and_cardinality(bytes: &[u8]) -> (usize, usize) {
+    let mut matches = 0;
+    let mut occupied = 0;
+    for_each_table_slot(bytes, |_, data, slot: &mut u32| {
+        let entry = data | OCCUPIED;
+        matches += (*slot == entry) as usize;
+        occupied += (*slot == 0) as usize;
+        *slot = entry;
+    });
+
+    (matches, linear_count(occupied))
+}
+
+/// Estimates the number of distinct items inserted into the table from its occupancy.
+///
+/// The estimate is undefined for a full table (`ln(0)`), so a full table is treated as having a
+/// single empty slot; the largest value it can estimate, `m * ln(m)` (~340K for 32768 slots).
+fn linear_count(occupied: usize) -> usize {
+    let slots = HASH_SIZE as f64;
+    let empty = (HASH_SIZE - occupied).max(1) as f64;
+    round(-slots * ln(empty / slots)) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn unique_3grams_have_high_cardinality_and_few_matches() {
+        // Every u16 once; 3-grams only repeat across the low/high byte boundary pattern.
+        let data: Vec<u8> = (0..u16::MAX).flat_map(|x| x.to_le_bytes()).collect();
+        let (matches, distinct) = estimate_match_and_cardinality(&data);
+
+        assert!(matches < data.len() / 100, "got {matches} matches");
+        assert!(distinct > 50_000, "got {distinct} distinct");
+    }
+
+    #[test]
+    fn repetitive_data_has_low_cardinality_and_many_matches() {
+        let data: Vec<u8> = (0..1 << 16).map(|x| (x % 64) as u8).collect();
+        let (matches, distinct) = estimate_match_and_cardinality(&data);
+
+        assert!(matches > data.len() - 128, "got {matches} matches");
+        assert!((60..=70).contains(&distinct), "got {distinct} distinct");
+    }
+
+    #[test]
+    fn full_table_saturates_at_one_empty_slot() {
+        assert_eq!(linear_count(0), 0);
+        assert_eq!(linear_count(HASH_SIZE), linear_count(HASH_SIZE - 1));
+        // 32768 * ln(32768)
+        assert_eq!(linear_count(HASH_SIZE), 340_696);
+    }
+
+    #[test]
+    fn tiny_inputs_have_nothing() {
+        for len in 0..=3 {
+            let data: Vec<u8> = (0..len).collect();
+            assert_eq!(estimate_match_and_cardinality(&data), (0, 0));
+        }
+    }
+}
@@ -0,0 +1,238 @@
+//! Reusable match estimator which keeps its hash table between calls.
+
+use super::{
+    estimate_num_lz_matches_with_table_bits, hash_table_layout_with_bits, MATCH_HASH_BITS,
+};
+use safe_allocator_api::prelude::{AllocError, Allocator, Global};
+use safe_allocator_api::RawAlloc;
+
+/// A reusable version of [`estimate_num_lz_matches_fast`], which owns its hash table.
+///
+/// [`estimate_num_lz_matches_fast`] allocates (and frees) a 128KiB table on every call; when
+/// estimating many small blocks, that allocation can cost more than the estimation itself.
+/// This struct allocates the table once and only clears it between calls.
+///
+/// The table is allocated through [`RawAlloc`] rather than `Box`/`Vec`, so this works in
+/// `no_std` environments with an allocator; and is freed when the estimator is dropped.
+///
+/// # Hash Table Size
+///
+/// `BITS` sets the size of the hash table to `1 << BITS` entries, 4 bytes each. The default of
+/// [`MATCH_HASH_BITS`] (15, i.e. 128KiB) is what [`estimate_num_lz_matches_fast`] uses; it's
+/// tuned for CPUs with a 32K L1 cache, and is the only size which can use the SIMD backends.
+/// Each extra bit doubles the table; so matches twice as far apart are found as often, at the
+/// cost of more cache misses. Use [`MatchEstimator::with_hash_bits`] to pick another size.
+///
+/// # Example
+///
+/// ```
+/// #![no_std]
+/// use lossless_transform_utils::match_estimator::MatchEstimator;
+///
+/// fn main() {
+///     let mut estimator = MatchEstimator::new();
+///     let first = estimator.estimate_num_lz_matches_fast(b"hello world hello world hello");
+///     let second = estimator.estimate_num_lz_matches_fast(b"hello world hello world hello");
+///
+///     // Table is cleared between calls, so results don't depend on previous inputs.
+///     assert_eq!(first, second);
+/// }
+/// ```
+///
+/// [`estimate_num_lz_matches_fast`]: super::estimate_num_lz_matches_fast
+pub struct MatchEstimator<A: Allocator = Global, const BITS: usize = MATCH_HASH_BITS> {
+    table: RawAlloc<A>,
+    /// Whether the table contains entries from a previous call.
+    dirty: bool,
+}
+
+impl MatchEstimator {
+    /// Creates a new estimator, allocating its hash table with the global allocator.
+    ///
+    /// # Panics
+    ///
+    /// If the allocation fails, in the same way as [`estimate_num_lz_matches_fast`] does.
+    ///
+    /// [`estimate_num_lz_matches_fast`]: super::estimate_num_lz_matches_fast
+    pub fn new() -> Self {
+        Self::new_in(Global).unwrap()
+    }
+}
+
+impl Default for MatchEstimator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const BITS: usize> MatchEstimator<Global, BITS> {
+    /// Creates a new estimator with a hash table of `1 << BITS` entries, allocating it with the
+    /// global allocator.
+    ///
+    /// # Panics
+    ///
+    /// If the allocation fails. At compile time, if `BITS` is not in `8..=24`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lossless_transform_utils::match_estimator::MatchEstimator;
+    ///
+    /// // 64K entries (256KiB); for CPUs with larger caches, or better long range accuracy.
+    /// let mut estimator: MatchEstimator<_, 16> = MatchEstimator::with_hash_bits();
+    /// assert!(estimator.estimate(b"hello world hello world hello") > 0);
+    /// ```
+    pub fn with_hash_bits() -> Self {
+        Self::with_hash_bits_in(Global).unwrap()
+    }
+}
+
+impl<A: Allocator> MatchEstimator<A> {
+    /// Creates a new estimator, allocating its hash table with a custom allocator.
+    ///
+    /// # Arguments
+    ///
+    /// * `allocator` - The allocator used to allocate (and free) the hash table.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AllocError`] if the allocator fails to provide the table.
+    pub fn new_in(allocator: A) -> Result<Self, AllocError> {
+        Self::with_hash_bits_in(allocator)
+    }
+}
+
+impl<A: Allocator, const BITS: usize> MatchEstimator<A, BITS> {
+    /// Creates a new estimator with a hash table of `1 << BITS` entries, allocating it with a
+    /// custom allocator.
+    ///
+    /// # Arguments
+    ///
+    /// * `allocator` - The allocator used to allocate (and free) the hash table.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AllocError`] if the allocator fails to provide the table.
+    ///
+    /// # Panics
+    ///
+    /// At compile time, if `BITS` is not in `8..=24`.
+    pub fn with_hash_bits_in(allocator: A) -> Result<Self, AllocError> {
+        const { assert!(BITS >= 8 && BITS <= 24, "BITS must be between 8 and 24") };
+
+        Ok(Self {
+            table: RawAlloc::new_zeroed_in(hash_table_layout_with_bits(BITS), allocator)?,
+            dirty: false,
+        })
+    }
+
+    /// Estimates the number of >=3 byte LZ matches in a given input data stream.
+    ///
+    /// With the default `BITS`, returns exactly the same result as
+    /// [`estimate_num_lz_matches_fast`], but reuses the estimator's hash table instead of
+    /// allocating a new one.
+    ///
+    /// [`estimate_num_lz_matches_fast`]: super::estimate_num_lz_matches_fast
+    pub fn estimate_num_lz_matches_fast(&mut self, bytes: &[u8]) -> usize {
+        let hash_table = self.clean_table();
+        estimate_num_lz_matches_with_table_bits::<BITS>(hash_table, bytes)
+    }
+
+    /// Estimates the number of >=3 byte LZ matches in a given input data stream.
+    ///
+    /// Shortcut for [`MatchEstimator::estimate_num_lz_matches_fast`]. The table is cleared with
+    /// a single `memset` before each run (only if a previous run used it), never reallocated.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lossless_transform_utils::match_estimator::MatchEstimator;
+    ///
+    /// let mut estimator = MatchEstimator::new();
+    /// let blocks: [&[u8]; 2] = [b"abcabcabcabcabc", b"no repeats here"];
+    /// let matches: usize = blocks.iter().map(|block| estimator.estimate(block)).sum();
+    /// ```
+    #[inline]
+    pub fn estimate(&mut self, bytes: &[u8]) -> usize {
+        self.estimate_num_lz_matches_fast(bytes)
+    }
+
+    /// Restores the estimator to its freshly constructed state, with an all zero hash table.
+    ///
+    /// Estimates never depend on previous calls, as the table is cleared lazily on the next
+    /// call; so this is never needed for correctness. It moves the cost of clearing the hash
+    /// table to now instead, e.g. to a point outside of a latency sensitive section. That's
+    /// still cheaper than dropping the estimator and creating a new one, as nothing is
+    /// reallocated.
+    pub fn reset(&mut self) {
+        if self.dirty {
+            self.table().fill(0);
+            self.dirty = false;
+        }
+    }
+
+    /// Returns the hash table, cleared of any entries from previous calls.
+    #[inline(always)]
+    pub(crate) fn clean_table(&mut self) -> &mut [u32] {
+        if self.dirty {
+            self.table().fill(0);
+        }
+        self.dirty = true;
+        self.table()
+    }
+
+    /// Returns the hash table as-is; `1 << BITS` entries.
+    #[inline(always)]
+    fn table(&mut self) -> &mut [u32] {
+        unsafe { core::slice::from_raw_parts_mut(self.table.as_mut_ptr() as *mut u32, 1 << BITS) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::match_estimator::estimate_num_lz_matches_fast;
+    use std::vec::Vec;
+
+    #[test]
+    fn reused_estimator_matches_one_shot_function() {
+        let repetitive: Vec<u8> = (0..1 << 16).map(|x| (x % 1000) as u8).collect();
+        let unique: Vec<u8> = (0..u16::MAX).flat_map(|x| x.to_le_bytes()).collect();
+
+        let mut estimator = MatchEstimator::new();
+        for data in [&repetitive, &unique, &repetitive] {
+            assert_eq!(
+                estimator.estimate_num_lz_matches_fast(data),
+                estimate_num_lz_matches_fast(data)
+            );
+        }
+    }
+
+    #[test]
+    fn can_use_custom_allocator() {
+        let mut estimator = MatchEstimator::new_in(Global).unwrap();
+        let data = b"hello world hello world hello";
+        assert_eq!(
+            estimator.estimate_num_lz_matches_fast(data),
+            estimate_num_lz_matches_fast(data)
+        );
+    }
+
+    #[test]
+    fn reset_estimator_matches_fresh_one() {
+        let repetitive: Vec<u8> = (0..1 << 16).map(|x| (x % 1000) as u8).collect();
+        let data = b"hello world hello world hello";
+
+        let mut estimator = MatchEstimator::new();
+        estimator.reset();
+        estimator.estimate_num_lz_matches_fast(&repetitive);
+        estimator.reset();
+        // Not dirty, so this returns the table as `reset` left it.
+        assert!(!estimator.dirty);
+        assert!(estimator.clean_table().iter().all(|&x| x == 0));
+        assert_eq!(
+            estimator.estimate_num_lz_matches_fast(data),
+            MatchEstimator::new().estimate_num_lz_matches_fast(data)
+        );
+    }
+}
@@ -6,13 +6,30 @@ use core::alloc::Layout;
 use safe_allocator_api::RawAlloc;
 #[cfg(all(
     any(target_arch = "x86", target_arch = "x86_64"),
-    any(feature = "estimator-avx512", feature = "estimator-avx2")
+    any(feature = "estimator-avx512", feature = "estimator-avx2"),
+    feature = "std"
 ))]
 use std::is_x86_feature_detected;
 
 #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
 #[cfg(feature = "estimator-avx2")]
 mod avx2;
+mod cardinality;
+pub use cardinality::*;
+mod estimator;
+pub use estimator::*;
+#[cfg(feature = "std")]
+mod profile;
+#[cfg(feature = "std")]
+pub use profile::*;
+mod range;
+pub use range::*;
+mod table;
+pub(crate) use table::*;
+mod variants;
+pub use variants::*;
+mod windowed;
+pub use windowed::*;
 #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
 #[cfg(feature = "estimator-avx512")]
 mod avx512;
@@ -83,8 +100,8 @@ const GOLDEN_RATIO: u32 = 0x9E3779B1_u32;
 //
 // Fun, semi-related reading: <https://en.algorithmica.org/hpc/cpu-cache/associativity/#hardware-caches>
 // And I found this after writing all this code: <https://probablydance.com/2018/06/16/fibonacci-hashing-the-optimization-that-the-world-forgot-or-a-better-alternative-to-integer-modulo/>
-const HASH_BITS: usize = 15; // 2^15 = 32k (of u32s) == 128KBytes
-const HASH_SIZE: usize = 1 << HASH_BITS;
+pub(crate) const HASH_BITS: usize = 15; // 2^15 = 32k (of u32s) == 128KBytes
+pub(crate) const HASH_SIZE: usize = 1 << HASH_BITS;
 #[allow(dead_code)]
 const HASH_MASK: u32 = (HASH_SIZE - 1) as u32;
 
@@ -109,11 +126,43 @@ const HASH_MASK: u32 = (HASH_SIZE - 1) as u32;
 /// Do note that this is an estimator; it is not an exact number; but the number should be accurate-ish
 /// given that we use 32-bit hashes (longer than 24-bit source). Think of this as equivalent to a
 /// 'fast mode'/low compression level mode.
+///
+/// Each call allocates (and frees) a 128KiB hash table. When estimating many blocks, create a
+/// [`MatchEstimator`] once and reuse it instead.
 pub fn estimate_num_lz_matches_fast(bytes: &[u8]) -> usize {
-    // This table stores 3 byte hashes, each hash is transformed
-    let layout = unsafe { Layout::from_size_align_unchecked(size_of::<u32>() * HASH_SIZE, 64) };
-    let mut alloc = RawAlloc::new_zeroed(layout).unwrap();
-    let hash_table = unsafe { &mut *(alloc.as_mut_ptr() as *mut [u32; HASH_SIZE]) };
+    let mut alloc = RawAlloc::new_zeroed(hash_table_layout_with_bits(HASH_BITS)).unwrap();
+    let table = unsafe { &mut *(alloc.as_mut_ptr() as *mut [u32; HASH_SIZE]) };
+
+    // Freshly zeroed, so no need to clear it.
+    estimate_num_lz_matches_fast_with_table(bytes, table, false)
+}
+
+/// Layout of a hash table of `1 << bits` entries; cache line aligned.
+#[inline(always)]
+pub(crate) fn hash_table_layout_with_bits(bits: usize) -> Layout {
+    unsafe { Layout::from_size_align_unchecked(size_of::<u32>() << bits, 64) }
+}
+
+/// Runs the estimator over `bytes` using an already zeroed `hash_table`.
+#[inline(always)]
+pub(crate) fn estimate_num_lz_matches_with_table(
+    hash_table: &mut [u32; HASH_SIZE],
+    bytes: &[u8],
+) -> usize {
+    estimate_num_lz_matches_with_table_bits::<HASH_BITS>(hash_table, bytes)
+}
+
+/// Runs the estimator over `bytes` using an already zeroed `hash_table` of `1 << BITS` entries.
+///
+/// Only the default of [`HASH_BITS`] can use the SIMD backends, which are written around its
+/// table size; other sizes always use the generic one.
+#[inline(always)]
+pub(crate) fn estimate_num_lz_matches_with_table_bits<const BITS: usize>(
+    hash_table: &mut [u32],
+    bytes: &[u8],
+) -> usize {
+    #[cfg(feature = "instrument")]
+    let start = std::time::Instant::now();
 
     let mut matches = 0;
     let begin_ptr = bytes.as_ptr();
@@ -122,12 +171,92 @@ pub fn estimate_num_lz_matches_fast(bytes: &[u8]) -> usize {
         //      +3 bytes for offset
         // We're dropping it, this is an estimation, after all.
         let end_ptr = begin_ptr.add(bytes.len().saturating_sub(7)); // min 0
-        calculate_matches_impl(hash_table, &mut matches, begin_ptr, end_ptr);
+        if BITS == HASH_BITS {
+            let hash_table = hash_table.try_into().unwrap();
+            calculate_matches_impl(hash_table, &mut matches, begin_ptr, end_ptr);
+        } else {
+            calculate_matches_generic_with_bits::<BITS>(
+                hash_table,
+                &mut matches,
+                begin_ptr,
+                end_ptr,
+            );
+        }
     }
 
+    #[cfg(feature = "instrument")]
+    crate::instrument::record_match_estimator(start, bytes.len());
+
     matches
 }
 
+/// Implementation used by the match estimator to process the input.
+///
+/// Every backend examines every position of the input, so their counts are comparable across
+/// machines as-is; no correction for skipped positions is needed. The counts are not
+/// identical though: the SIMD backends look up a batch of 32 positions before inserting any of
+/// them into the table, so matches against an earlier position in the same batch (e.g. within a
+/// short run) are missed. On mixed data this puts them within ~2% of [`MatchBackend::Generic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchBackend {
+    /// Portable scalar implementation; any CPU.
+    Generic,
+    /// AVX2 implementation; enabled by the `estimator-avx2` feature.
+    ///
+    /// Also used on AVX512 machines when `estimator-avx512` is not enabled.
+    Avx2,
+    /// AVX512 implementation; enabled by the `estimator-avx512` feature.
+    Avx512,
+}
+
+/// Returns the backend the match estimator uses on the current machine.
+///
+/// The fastest enabled backend the CPU supports wins. Each check is independent of the others;
+/// so with only `estimator-avx2` enabled (e.g. on a stable toolchain, as `estimator-avx512`
+/// requires nightly), AVX512 capable machines use [`MatchBackend::Avx2`], since every such
+/// CPU also supports AVX2.
+#[inline(always)]
+pub(crate) fn match_backend() -> MatchBackend {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        #[cfg(feature = "estimator-avx512")]
+        if has_avx512() {
+            return MatchBackend::Avx512;
+        }
+
+        #[cfg(feature = "estimator-avx2")]
+        if has_avx2() {
+            return MatchBackend::Avx2;
+        }
+    }
+
+    MatchBackend::Generic
+}
+
+/// Whether the AVX512 backend can run. Without `std` there's no runtime feature detection, so
+/// this is only true if the crate was compiled with the target features enabled.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[cfg(feature = "estimator-avx512")]
+#[inline(always)]
+fn has_avx512() -> bool {
+    #[cfg(feature = "std")]
+    return is_x86_feature_detected!("avx512f") && is_x86_feature_detected!("avx512vl");
+    #[cfg(not(feature = "std"))]
+    return cfg!(all(target_feature = "avx512f", target_feature = "avx512vl"));
+}
+
+/// Whether the AVX2 backend can run. Without `std`, only true if the crate was compiled with the
+/// `avx2` target feature enabled.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[cfg(feature = "estimator-avx2")]
+#[inline(always)]
+fn has_avx2() -> bool {
+    #[cfg(feature = "std")]
+    return is_x86_feature_detected!("avx2");
+    #[cfg(not(feature = "std"))]
+    return cfg!(target_feature = "avx2");
+}
+
 #[inline(always)]
 fn calculate_matches_impl(
     hash_table: &mut [u32; HASH_SIZE],
@@ -135,10 +264,13 @@ fn calculate_matches_impl(
     begin_ptr: *const u8,
     end_ptr: *const u8,
 ) {
+    #[allow(unused_variables)]
+    let backend = match_backend();
+
     #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
     {
         #[cfg(feature = "estimator-avx512")]
-        if is_x86_feature_detected!("avx512f") && is_x86_feature_detected!("avx512vl") {
+        if backend == MatchBackend::Avx512 {
             unsafe {
                 avx512::calculate_matches_avx512(hash_table, matches, begin_ptr, end_ptr);
                 return;
@@ -146,7 +278,7 @@ fn calculate_matches_impl(
         }
 
         #[cfg(feature = "estimator-avx2")]
-        if is_x86_feature_detected!("avx2") {
+        if backend == MatchBackend::Avx2 {
             unsafe {
                 avx2::calculate_matches_avx2(hash_table, matches, begin_ptr, end_ptr);
                 return;
@@ -164,6 +296,30 @@ fn calculate_matches_impl(
 pub(crate) unsafe fn calculate_matches_generic(
     hash_table: &mut [u32; HASH_SIZE],
     matches: &mut usize,
+    begin_ptr: *const u8,
+    end_ptr: *const u8,
+) {
+    calculate_matches_generic_loop::<HASH_BITS>(hash_table, matches, begin_ptr, end_ptr);
+}
+
+// Generic, for any CPU; with a table of `1 << BITS` entries.
+#[inline(never)] // try reduce register pressure
+pub(crate) unsafe fn calculate_matches_generic_with_bits<const BITS: usize>(
+    hash_table: &mut [u32],
+    matches: &mut usize,
+    begin_ptr: *const u8,
+    end_ptr: *const u8,
+) {
+    // Every index is `< 1 << BITS`; knowing the length lets the compiler drop the bounds checks.
+    assert_eq!(hash_table.len(), 1 << BITS);
+    calculate_matches_generic_loop::<BITS>(hash_table, matches, begin_ptr, end_ptr);
+}
+
+// Loop shared by the above; inlined into each, so the 15 bit one indexes a fixed size array.
+#[inline(always)]
+unsafe fn calculate_matches_generic_loop<const BITS: usize>(
+    hash_table: &mut [u32],
+    matches: &mut usize,
     mut begin_ptr: *const u8,
     end_ptr: *const u8,
 ) {
@@ -200,16 +356,16 @@ pub(crate) unsafe fn calculate_matches_generic(
         // as I call it https://probablydance.com/wp-content/uploads/2018/06/avalanche_fibonacci1.png
         // near the top bits.
 
-        // We get rid of this 'sea of red' by shifting right `32 - HASH_BITS`, and then AND-ing
+        // We get rid of this 'sea of red' by shifting right `32 - BITS`, and then AND-ing
         // to fit our value in the mask. I'm not sure if it's the article not specifying bit
         // order here, or whether my results are 'backwards',
 
-        // Use HASH_MASK bits for index into HASH_SIZE table
-        // Note: We don't need to AND with HASH_MASK because we're only taking upper bits.
-        let index0 = (h0 >> (32 - HASH_BITS)) as usize;
-        let index1 = (h1 >> (32 - HASH_BITS)) as usize;
-        let index2 = (h2 >> (32 - HASH_BITS)) as usize;
-        let index3 = (h3 >> (32 - HASH_BITS)) as usize;
+        // Use BITS bits for index into `1 << BITS` table
+        // Note: We don't need to AND with a mask because we're only taking upper bits.
+        let index0 = (h0 >> (32 - BITS)) as usize;
+        let index1 = (h1 >> (32 - BITS)) as usize;
+        let index2 = (h2 >> (32 - BITS)) as usize;
+        let index3 = (h3 >> (32 - BITS)) as usize;
 
         // Increment matches if the 32-bit data at the table matches
         // (which indicates a very likely LZ match)
@@ -226,14 +382,91 @@ pub(crate) unsafe fn calculate_matches_generic(
     }
 }
 
-/// Hashes a 32-bit value by multiplying it with the golden ratio,
-/// ensuring that it
+/// Hashes a 32-bit value by multiplying it with the golden ratio.
+///
+/// The upper bits of the result are the well scattered ones; use them as the table index, as
+/// [`probe_and_update`] does. The hash is a bijection, so two hashes are equal only if the
+/// hashed values are.
 #[inline(always)]
-#[allow(dead_code)]
-pub(crate) fn hash_u32(value: u32) -> u32 {
+pub fn hash_u32(value: u32) -> u32 {
     value.wrapping_mul(GOLDEN_RATIO)
 }
 
+/// Returns the estimator's hash of the 3 bytes starting at `pos`.
+///
+/// This is exactly the hash the estimator computes for that position: the 3 bytes are read as a
+/// little endian 24-bit value, and passed through [`hash_u32`]. The top 15 bits
+/// select the slot in a table of [`MATCH_TABLE_LEN`] entries, as in [`probe_and_update`]. Useful
+/// for lining up an external rolling hash (e.g. a chunker's) with the estimator's table.
+///
+/// # Returns
+///
+/// The hash, or [`None`] if fewer than 3 bytes remain at `pos`.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::match_estimator::{hash_u32, hash_window_at};
+///
+/// let data = b"abcabc";
+/// assert_eq!(hash_window_at(data, 0), Some(hash_u32(0x636261)));
+/// assert_eq!(hash_window_at(data, 0), hash_window_at(data, 3));
+/// assert_eq!(hash_window_at(data, 4), None);
+/// ```
+#[inline(always)]
+pub fn hash_window_at(bytes: &[u8], pos: usize) -> Option<u32> {
+    let window = bytes.get(pos..pos.checked_add(3)?)?;
+    let value = u32::from_le_bytes([window[0], window[1], window[2], 0]);
+    Some(hash_u32(value))
+}
+
+/// Performs a single lookup + insert into a match finder hash table; the scalar inner step of
+/// the match estimator.
+///
+/// The top `bits` bits of `hash` select the slot. If the slot already holds `hash`, it's a
+/// match. The slot is then overwritten with `hash`, so the next probe with the same hash matches.
+///
+/// As [`hash_u32`] is a bijection, storing the hash instead of the hashed data loses nothing;
+/// comparing hashes is the same as comparing the data. Feeding it `hash_u32` of the 3 bytes at
+/// each position (with a table of [`MATCH_TABLE_LEN`] entries and `bits` of 15) reproduces the
+/// counts of [`estimate_num_lz_matches_fast`]; except that the estimator looks up 4 positions
+/// before inserting any of them, so it misses matches between positions in the same group.
+///
+/// # Arguments
+///
+/// * `table` - The hash table. Starts zeroed; so a hash of 0 matches an unused slot.
+/// * `hash` - Hash of the data at the current position, e.g. from [`hash_u32`].
+/// * `bits` - Number of bits used to index the table.
+///
+/// # Returns
+///
+/// `true` if the slot already held `hash`.
+///
+/// # Safety
+///
+/// `bits` must be in `1..=32`, and `table` must have at least `1 << bits` entries. Neither is
+/// checked; the index is not bounds checked.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::match_estimator::{hash_u32, probe_and_update};
+///
+/// let mut table = vec![0u32; 1 << 12];
+/// unsafe {
+///     assert!(!probe_and_update(&mut table, hash_u32(0x636261), 12));
+///     assert!(probe_and_update(&mut table, hash_u32(0x636261), 12));
+/// }
+/// ```
+#[inline(always)]
+pub unsafe fn probe_and_update(table: &mut [u32], hash: u32, bits: usize) -> bool {
+    debug_assert!((1..=32).contains(&bits) && table.len() as u64 >= 1_u64 << bits);
+    let slot = table.get_unchecked_mut((hash >> (32 - bits)) as usize);
+    let is_match = *slot == hash;
+    *slot = hash;
+    is_match
+}
+
 /// Reads a 3 byte value from a 32-bit unaligned pointer.
 ///
 /// # Safety
@@ -263,6 +496,155 @@ mod tests {
     use std::vec::Vec;
     use std::{println, vec};
 
+    type Backend = unsafe fn(&mut [u32; HASH_SIZE], &mut usize, *const u8, *const u8);
+
+    /// All backends which can run on the current machine.
+    fn available_backends() -> Vec<(&'static str, Backend)> {
+        #[allow(unused_mut)]
+        let mut backends: Vec<(&'static str, Backend)> =
+            vec![("generic", calculate_matches_generic)];
+
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        {
+            #[cfg(feature = "estimator-avx2")]
+            if is_x86_feature_detected!("avx2") {
+                backends.push(("avx2", avx2::calculate_matches_avx2));
+            }
+
+            #[cfg(feature = "estimator-avx512")]
+            if is_x86_feature_detected!("avx512f") && is_x86_feature_detected!("avx512vl") {
+                backends.push(("avx512", avx512::calculate_matches_avx512));
+            }
+        }
+
+        backends
+    }
+
+    fn run_backend(backend: Backend, bytes: &[u8]) -> usize {
+        let mut table = vec![0u32; HASH_SIZE];
+        let hash_table: &mut [u32; HASH_SIZE] = table.as_mut_slice().try_into().unwrap();
+        let mut matches = 0;
+        unsafe {
+            let begin_ptr = bytes.as_ptr();
+            let end_ptr = begin_ptr.add(bytes.len().saturating_sub(7));
+            backend(hash_table, &mut matches, begin_ptr, end_ptr);
+        }
+        matches
+    }
+
+    #[test]
+    fn all_backends_handle_degenerate_small_inputs() {
+        // Repeats every 32 bytes, with no zero bytes (which would hit the empty table) and no
+        // repeats within a period; so every backend should find exactly the same matches.
+        let data: Vec<u8> = (0..64).map(|x| (x % 32 + 1) as u8).collect();
+
+        for (name, backend) in available_backends() {
+            for len in 0..=7 {
+                assert_eq!(run_backend(backend, &data[..len]), 0, "{name}, len {len}");
+            }
+
+            for len in 8..=64 {
+                assert_eq!(
+                    run_backend(backend, &data[..len]),
+                    run_backend(calculate_matches_generic, &data[..len]),
+                    "{name}, len {len}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    #[cfg(any(feature = "estimator-avx2", feature = "estimator-avx512"))]
+    fn all_backends_give_comparable_counts() {
+        use crate::test_utils::random_bytes;
+
+        // Every backend examines the same positions; they only differ in how many positions
+        // are looked up before the table is updated, so counts should agree closely.
+        // Each chunk takes 4 random bytes, and adds at least 13 bytes.
+        let random = random_bytes(1 << 18, 12345);
+        let mut states = random
+            .chunks_exact(4)
+            .map(|x| u32::from_le_bytes([x[0], x[1], x[2], x[3]]));
+        let mut data = Vec::with_capacity(1 << 18);
+        while data.len() < 1 << 18 {
+            let state = states.next().unwrap();
+            match state >> 30 {
+                0 => data.extend_from_slice(b"the quick brown fox jumps over the lazy dog. "),
+                1 => data.extend_from_slice(&[(state >> 8) as u8; 13]),
+                _ => data.extend((0..16).map(|x| (state >> (x % 24)) as u8)),
+            }
+        }
+
+        let expected = run_backend(calculate_matches_generic, &data);
+        for (name, backend) in available_backends() {
+            let matches = run_backend(backend, &data);
+            let error = (matches as f64 - expected as f64).abs() / expected as f64;
+            assert!(error < 0.02, "{name}: {matches} vs {expected}");
+        }
+    }
+
+    #[test]
+    fn all_backends_match_generic_exactly_without_short_repeats() {
+        // Repeats every 97 bytes; further apart than any backend's batch of positions, so no
+        // backend misses a match by looking up a batch before inserting it. No zero bytes, so
+        // the empty table never matches.
+        let data: Vec<u8> = (0..1 << 16).map(|x| (x % 97 + 1) as u8).collect();
+
+        // Every 3 byte sequence must have its own slot; otherwise the order in which a batch
+        // updates the table could decide which of two colliding sequences is remembered.
+        let mut slots: Vec<usize> = (0..97)
+            .map(|x| (hash_window_at(&data, x).unwrap() >> (32 - HASH_BITS)) as usize)
+            .collect();
+        slots.sort_unstable();
+        slots.dedup();
+        assert_eq!(slots.len(), 97);
+
+        let expected = run_backend(calculate_matches_generic, &data);
+        assert!(expected > 0);
+        for (name, backend) in available_backends() {
+            assert_eq!(run_backend(backend, &data), expected, "{name}");
+        }
+
+        // And the dispatched entry point, whichever backend it picked.
+        assert_eq!(estimate_num_lz_matches_fast(&data), expected);
+    }
+
+    #[test]
+    fn probe_and_update_reproduces_generic_estimator() {
+        let data: Vec<u8> = (0..1 << 16).map(|x| (x % 1000) as u8).collect();
+        let mut table = vec![0u32; HASH_SIZE];
+
+        // The estimator processes positions in groups of 4, until 7 bytes before the end.
+        // This data has no matches within a group, so probing one at a time gives the same count.
+        let positions = data.len().saturating_sub(7).div_ceil(4) * 4;
+        let matches = (0..positions)
+            .filter(|&position| {
+                let bytes = data[position..position + 4].try_into().unwrap();
+                let value = reduce_to_3byte(u32::from_le_bytes(bytes));
+                unsafe { probe_and_update(&mut table, hash_u32(value), HASH_BITS) }
+            })
+            .count();
+
+        assert_eq!(matches, run_backend(calculate_matches_generic, &data));
+    }
+
+    #[test]
+    fn hash_window_at_matches_generic_estimator_hashes() {
+        let data: Vec<u8> = (0..4096).map(|x| (x * 7 % 251) as u8).collect();
+        for position in 0..data.len() - 3 {
+            let value = unsafe { read_4_byte_le_unaligned(data.as_ptr(), position) };
+            assert_eq!(
+                hash_window_at(&data, position),
+                Some(hash_u32(reduce_to_3byte(value)))
+            );
+        }
+
+        assert!(hash_window_at(&data, data.len() - 3).is_some());
+        assert_eq!(hash_window_at(&data, data.len() - 2), None);
+        assert_eq!(hash_window_at(&data, usize::MAX), None);
+    }
+
     #[test]
     fn can_hash_u32() {
         // Test that different inputs produce different hashes
@@ -273,6 +655,18 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    #[cfg(all(feature = "estimator-avx2", not(feature = "estimator-avx512")))]
+    fn avx2_backend_is_selected_on_all_avx2_machines() {
+        // Including AVX512 ones; the AVX2 check doesn't depend on the absence of AVX512.
+        if is_x86_feature_detected!("avx2") {
+            assert_eq!(match_backend(), MatchBackend::Avx2);
+        } else {
+            assert_eq!(match_backend(), MatchBackend::Generic);
+        }
+    }
+
     #[test]
     fn is_zero_on_empty_input() {
         let empty: Vec<u8> = vec![];
@@ -365,6 +759,14 @@ mod tests {
         #[case] test_size: usize,
         #[case] match_interval: usize,
         #[case] min_matches: usize,
+        // The minimums and percentages above were measured with the default 15 bit table.
+        // Smaller tables find fewer long range matches; the 14 bit one still meets every minimum.
+        #[values(
+            estimate_num_lz_matches_fast,
+            estimate_with_bits::<14>,
+            estimate_with_bits::<16>
+        )]
+        estimate: fn(&[u8]) -> usize,
     ) {
         assert!(
             match_interval <= 1 << 16,
@@ -393,7 +795,7 @@ mod tests {
         // Bytes 32768-49151: [00 00, 01 00, 02 00, ..., FF 3F]  Third cycle
         // And so on...
 
-        let matches = estimate_num_lz_matches_fast(cast_u16_slice_to_u8_slice(&unique));
+        let matches = estimate(cast_u16_slice_to_u8_slice(&unique));
 
         // After first match_interval bytes, every position matches with match_interval bytes before it
         let expected = test_size - match_interval;
@@ -412,6 +814,11 @@ mod tests {
         ); // cargo test -- --nocapture | grep -i "^\[res:"
     }
 
+    fn estimate_with_bits<const BITS: usize>(bytes: &[u8]) -> usize {
+        let mut estimator: MatchEstimator<_, BITS> = MatchEstimator::with_hash_bits();
+        estimator.estimate(bytes)
+    }
+
     fn cast_u16_slice_to_u8_slice(u16_slice: &[u16]) -> &[u8] {
         let ptr = u16_slice.as_ptr() as *const u8;
         let len = u16_slice.len() * 2; // Each u16 is 2 bytes
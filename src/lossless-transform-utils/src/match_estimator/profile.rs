@@ -0,0 +1,122 @@
+//! Match estimation over consecutive windows of the input.
+
+use super::{
+    estimate_num_lz_matches_with_table, hash_table_layout_with_bits, HASH_BITS, HASH_SIZE,
+};
+use safe_allocator_api::RawAlloc;
+use std::vec::Vec;
+
+/// Estimates the number of >=3 byte LZ matches in each consecutive `window` sized chunk of the
+/// input, showing where the data is more (or less) redundant.
+///
+/// Useful for adaptive block splitting; e.g. placing block boundaries where the density of
+/// matches changes sharply.
+///
+/// # Arguments
+///
+/// * `bytes` - The input data stream.
+/// * `window` - Size of each window in bytes. The last window may be shorter.
+/// * `carry_table` - Whether to keep the hash table between windows.
+///   - `false`: the table is cleared for each window, so each count is what
+///     [`estimate_num_lz_matches_fast`] returns for that window alone; only matches within the
+///     window are counted. Use this when each window will be compressed independently.
+///   - `true`: the table is kept, so matches against data in earlier windows are counted too.
+///     Use this when the windows are parts of one compressed stream.
+///
+/// # Returns
+///
+/// The estimated match count of each window, in order. Empty if `bytes` is empty.
+///
+/// # Remarks
+///
+/// As with [`estimate_num_lz_matches_fast`], the last 7 bytes of each window are not hashed;
+/// so with small windows, the counts are a noticeably lower fraction of the window size.
+///
+/// # Panics
+///
+/// If `window` is 0.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::match_estimator::match_density_profile;
+///
+/// let mut data = vec![0xAB_u8; 4096];
+/// data.extend((0..4096_u32).map(|x| (x.wrapping_mul(0x9E3779B1) >> 24) as u8));
+///
+/// let profile = match_density_profile(&data, 4096, false);
+/// assert_eq!(profile.len(), 2);
+/// assert!(profile[0] > profile[1]);
+/// ```
+///
+/// [`estimate_num_lz_matches_fast`]: super::estimate_num_lz_matches_fast
+pub fn match_density_profile(bytes: &[u8], window: usize, carry_table: bool) -> Vec<usize> {
+    assert!(window > 0, "window must be non-zero");
+
+    let mut alloc = RawAlloc::new_zeroed(hash_table_layout_with_bits(HASH_BITS)).unwrap();
+    let hash_table = unsafe { &mut *(alloc.as_mut_ptr() as *mut [u32; HASH_SIZE]) };
+
+    let mut profile = Vec::with_capacity(bytes.len().div_ceil(window));
+    for (index, chunk) in bytes.chunks(window).enumerate() {
+        if !carry_table && index > 0 {
+            hash_table.fill(0);
+        }
+        profile.push(estimate_num_lz_matches_with_table(hash_table, chunk));
+    }
+
+    profile
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::match_estimator::estimate_num_lz_matches_fast;
+    use crate::test_utils::random_bytes;
+
+    const WINDOW: usize = 4096;
+
+    /// 64KiB repeating every 1000 bytes, followed by 64KiB of pseudo random bytes.
+    fn redundant_then_random() -> Vec<u8> {
+        let mut data: Vec<u8> = (0..1 << 16).map(|x| (x % 1000) as u8).collect();
+        data.extend(random_bytes(1 << 16, 12345));
+        data
+    }
+
+    #[test]
+    fn profile_drops_in_random_half() {
+        let data = redundant_then_random();
+        let profile = match_density_profile(&data, WINDOW, false);
+        assert_eq!(profile.len(), 32);
+
+        let (redundant, random) = profile.split_at(16);
+        assert!(redundant.iter().all(|&x| x > WINDOW / 2), "{profile:?}");
+        assert!(random.iter().all(|&x| x < WINDOW / 100), "{profile:?}");
+    }
+
+    #[test]
+    fn reset_windows_match_independent_estimates() {
+        let data = redundant_then_random();
+        let profile = match_density_profile(&data, WINDOW, false);
+        for (chunk, &matches) in data.chunks(WINDOW).zip(&profile) {
+            assert_eq!(matches, estimate_num_lz_matches_fast(chunk));
+        }
+    }
+
+    #[test]
+    fn carried_table_finds_matches_across_windows() {
+        let data = redundant_then_random();
+        let reset = match_density_profile(&data, WINDOW, false);
+        let carried = match_density_profile(&data, WINDOW, true);
+
+        assert_eq!(reset[0], carried[0]);
+        let windows = carried.iter().zip(&reset).enumerate();
+        for (window, (carried, reset)) in windows.take(16).skip(1) {
+            assert!(carried > reset, "window {window}");
+        }
+    }
+
+    #[test]
+    fn empty_input_has_empty_profile() {
+        assert!(match_density_profile(&[], WINDOW, true).is_empty());
+    }
+}
@@ -0,0 +1,187 @@
+//! Match estimation which knows the distance between each match and its source.
+
+use super::{for_each_table_slot, OCCUPIED};
+
+/// Estimates the number of >=3 byte LZ matches in the input, split into short range and long
+/// range matches.
+///
+/// Each table slot stores the position a 3 byte sequence was last seen at alongside the data,
+/// so the distance of every match is known. This is useful for tuning the window size of a
+/// compressor: if most matches are long range, a small window will miss them.
+///
+/// # Arguments
+///
+/// * `bytes` - The input data stream.
+/// * `boundary` - The largest distance (in bytes) still considered a short range match.
+///
+/// # Returns
+///
+/// A tuple of (matches with distance <= `boundary`, matches with distance > `boundary`).
+///
+/// # Remarks
+///
+/// Only the most recent occurrence of each 3 byte sequence is remembered, so the distance is
+/// always the one to the nearest previous occurrence; i.e. what a greedy LZ match finder with
+/// an unlimited window would pick. Sequences hashing to the same slot evict each other, which
+/// hides some matches and makes others appear further away than they are (an older occurrence
+/// may be found after a newer one was evicted). This gets worse as the number of distinct
+/// 3 byte sequences approaches the 32768 slot table size, so for very diverse inputs the long
+/// range count is an overestimate and the total an underestimate.
+///
+/// Positions are stored as 32 bits; distances of 4GiB or more are therefore misreported.
+///
+/// The total of both counts matches the match count of
+/// [`estimate_match_and_cardinality`], since both hash every position in order.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::match_estimator::estimate_matches_by_range;
+///
+/// // "abc" repeats every 3 bytes, so every match is at distance 3.
+/// assert_eq!(estimate_matches_by_range(b"abcabcabcabc", 3), (6, 0));
+/// assert_eq!(estimate_matches_by_range(b"abcabcabcabc", 2), (0, 6));
+/// ```
+///
+/// [`estimate_match_and_cardinality`]: super::estimate_match_and_cardinality
+pub fn estimate_matches_by_range(bytes: &[u8], boundary: usize) -> (usize, usize) {
+    let mut short = 0;
+    let mut long = 0;
+    for_each_match_distance(bytes, |distance| {
+        if distance <= boundary {
+            short += 1;
+        } else {
+            long += 1;
+        }
+    });
+
+    (short, long)
+}
+
+/// Estimates the number of >=3 byte LZ matches in the input, only counting matches whose source
+/// is at least `min_distance` bytes back.
+///
+/// Matches at very short distances (1 or 2 bytes) are runs of a repeated byte (or byte pair);
+/// those are better handled by run-length encoding than by LZ. Passing a `min_distance` of 3
+/// counts only the matches which LZ is actually needed for.
+///
+/// # Arguments
+///
+/// * `bytes` - The input data stream.
+/// * `min_distance` - The smallest distance (in bytes) a match is counted at. 0 or 1 count
+///   every match.
+///
+/// # Returns
+///
+/// The estimated number of >=3 byte LZ matches at a distance of at least `min_distance`.
+///
+/// # Remarks
+///
+/// Only the distance to the nearest previous occurrence of each 3 byte sequence is known. If
+/// that's below `min_distance`, the match isn't counted; even if an LZ compressor could have
+/// used an older occurrence further back instead. The hash collision caveats of
+/// [`estimate_matches_by_range`] apply too.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::match_estimator::estimate_num_lz_matches_min_distance;
+///
+/// // A run is all distance 1 matches.
+/// assert_eq!(estimate_num_lz_matches_min_distance(&[7u8; 64], 1), 60);
+/// assert_eq!(estimate_num_lz_matches_min_distance(&[7u8; 64], 2), 0);
+/// ```
+pub fn estimate_num_lz_matches_min_distance(bytes: &[u8], min_distance: usize) -> usize {
+    let mut matches = 0;
+    for_each_match_distance(bytes, |distance| {
+        matches += (distance >= min_distance) as usize;
+    });
+
+    matches
+}
+
+/// Hashes every position of `bytes` in order, calling `on_match` with the distance of each match.
+#[inline(always)]
+fn for_each_match_distance(bytes: &[u8], mut on_match: impl FnMut(usize)) {
+    // Low 32 bits: 3 byte data | OCCUPIED. High 32 bits: position it was last seen at.
+    for_each_table_slot(bytes, |position, data, slot: &mut u64| {
+        let data = data | OCCUPIED;
+        if *slot as u32 == data {
+            on_match((position as u32).wrapping_sub((*slot >> 32) as u32) as usize);
+        }
+
+        *slot = ((position as u64) << 32) | data as u64;
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::match_estimator::estimate_match_and_cardinality;
+    use crate::test_utils::random_bytes;
+    use std::vec::Vec;
+
+    /// A block of pseudo random bytes, repeated `repeats` times.
+    fn repeating_block(block_len: usize, repeats: usize) -> Vec<u8> {
+        random_bytes(block_len, 12345).repeat(repeats)
+    }
+
+    #[test]
+    fn fixed_interval_splits_at_boundary() {
+        let interval = 4096;
+        let data = repeating_block(interval, 32);
+        let expected = data.len() - interval - 3;
+
+        // Every repeat is exactly `interval` bytes back; only a handful of chance repeats of
+        // random 3-grams within a block land elsewhere. The 4096 distinct 3-grams of a block
+        // collide in the 32768 slot table though, hiding ~1/8 of the matches.
+        let (short, long) = estimate_matches_by_range(&data, interval);
+        assert!(short >= expected * 8 / 10, "got {short} short");
+        assert!(long < expected / 100, "got {long} long");
+
+        let (short, long) = estimate_matches_by_range(&data, interval - 1);
+        assert!(long >= expected * 8 / 10, "got {long} long");
+        assert!(short < expected / 100, "got {short} short");
+    }
+
+    #[test]
+    fn total_matches_combined_estimator() {
+        let data = repeating_block(1000, 64);
+        let (short, long) = estimate_matches_by_range(&data, 500);
+        let (matches, _) = estimate_match_and_cardinality(&data);
+        assert_eq!(short + long, matches);
+    }
+
+    #[test]
+    fn min_distance_drops_sharply_on_runs() {
+        // Runs of 50 pseudo random bytes; nearly every match is at distance 1.
+        let mut data = Vec::new();
+        for byte in random_bytes(1000, 12345) {
+            data.extend_from_slice(&[byte; 50]);
+        }
+
+        let all = estimate_num_lz_matches_min_distance(&data, 1);
+        let non_rle = estimate_num_lz_matches_min_distance(&data, 2);
+        assert_eq!(estimate_num_lz_matches_min_distance(&data, 0), all);
+        assert!(all > data.len() * 9 / 10, "got {all}");
+        // Only the start of a run of a byte value seen in an earlier run remains.
+        assert!(non_rle < all / 20, "got {non_rle} vs {all}");
+    }
+
+    #[test]
+    fn min_distance_equals_long_range_split() {
+        let data = repeating_block(1000, 64);
+        let (short, long) = estimate_matches_by_range(&data, 999);
+        assert_eq!(estimate_num_lz_matches_min_distance(&data, 1000), long);
+        assert_eq!(estimate_num_lz_matches_min_distance(&data, 0), short + long);
+    }
+
+    #[test]
+    fn tiny_inputs_have_no_matches() {
+        for len in 0..=3 {
+            let data: Vec<u8> = (0..len).collect();
+            assert_eq!(estimate_matches_by_range(&data, 0), (0, 0));
+            assert_eq!(estimate_num_lz_matches_min_distance(&data, 0), 0);
+        }
+    }
+}
@@ -0,0 +1,36 @@
+//! The hash table walk shared by the single pass estimators which keep their own per-slot state.
+
+use super::{hash_u32, read_4_byte_le_unaligned, reduce_to_3byte, HASH_BITS, HASH_SIZE};
+use core::alloc::Layout;
+use safe_allocator_api::RawAlloc;
+
+/// Set on table entries which store 3 byte data, so an occupied slot is never 0 and can be told
+/// apart from an empty one; even when the stored 3 bytes are all zero. (3-byte values never use
+/// this bit.)
+pub(crate) const OCCUPIED: u32 = 1 << 24;
+
+/// Entry types of the table made by [`for_each_table_slot`]; all are valid when zeroed.
+pub(crate) trait TableEntry: Copy {}
+impl TableEntry for u32 {}
+impl TableEntry for u64 {}
+
+/// Hashes every position of `bytes` in order, calling `visit` with the position, the 3 bytes
+/// starting there (little endian) and that sequence's slot in a zeroed table of [`HASH_SIZE`]
+/// entries.
+///
+/// The last 3 positions are never visited, as every position is read as a 4 byte value.
+#[inline(always)]
+pub(crate) fn for_each_table_slot<T: TableEntry>(
+    bytes: &[u8],
+    mut visit: impl FnMut(usize, u32, &mut T),
+) {
+    let layout = Layout::from_size_align(size_of::<T>() * HASH_SIZE, 64).unwrap();
+    let mut alloc = RawAlloc::new_zeroed(layout).unwrap();
+    let hash_table = unsafe { &mut *(alloc.as_mut_ptr() as *mut [T; HASH_SIZE]) };
+
+    for position in 0..bytes.len().saturating_sub(3) {
+        let data = unsafe { reduce_to_3byte(read_4_byte_le_unaligned(bytes.as_ptr(), position)) };
+        let index = (hash_u32(data) >> (32 - HASH_BITS)) as usize;
+        visit(position, data, &mut hash_table[index]);
+    }
+}
@@ -0,0 +1,423 @@
+//! Convenience variants of [`estimate_num_lz_matches_fast`], for specific use cases.
+
+use super::{
+    estimate_num_lz_matches_fast, estimate_num_lz_matches_with_table,
+    estimate_num_lz_matches_with_table_bits, MatchEstimator, HASH_BITS, HASH_SIZE,
+};
+use crate::histogram::Histogram32;
+
+/// Estimates the number of >=3 byte LZ matches in a given input data stream, examining at most
+/// `max_positions` positions; bounding the time taken regardless of the input size.
+///
+/// # Arguments
+///
+/// * `bytes` - The input data stream.
+/// * `max_positions` - Maximum number of positions to hash. Rounded down to a multiple of 4, as
+///   the estimator processes 4 positions at a time.
+///
+/// # Returns
+///
+/// A tuple of (estimated match count, whether the whole input was examined). The whole input is
+/// examined if it has at most `max_positions` positions; [`estimate_num_lz_matches_fast`] only
+/// hashes `bytes.len() - 7` positions, so that's up to `max_positions + 7` bytes.
+///
+/// # Remarks
+///
+/// When truncated, only a prefix of the input is examined; so the count is exactly what
+/// [`estimate_num_lz_matches_fast`] returns for that prefix, and a lower bound of what it
+/// returns for the whole input.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::match_estimator::estimate_num_lz_matches_fast_limited;
+///
+/// let data = [0xAB_u8; 1 << 20];
+/// let (matches, completed) = estimate_num_lz_matches_fast_limited(&data, 4096);
+/// assert!(!completed);
+/// assert!(matches <= 4096);
+/// ```
+pub fn estimate_num_lz_matches_fast_limited(bytes: &[u8], max_positions: usize) -> (usize, bool) {
+    if bytes.len().saturating_sub(7) <= max_positions {
+        return (estimate_num_lz_matches_fast(bytes), true);
+    }
+
+    let prefix = &bytes[..(max_positions & !3) + 7];
+    (estimate_num_lz_matches_fast(prefix), false)
+}
+
+/// Estimates the number of >=3 byte LZ matches, using an already computed histogram of `bytes`
+/// to skip the estimator entirely for single symbol inputs.
+///
+/// If the histogram contains only a single distinct byte value, every position after the first
+/// 3 bytes is a match; so this returns `bytes.len() - 3` without scanning the data. Otherwise
+/// this is identical to [`estimate_num_lz_matches_fast`].
+///
+/// Useful in pipelines which already compute a histogram for each block, e.g. for entropy.
+///
+/// # Arguments
+///
+/// * `bytes` - The input data stream.
+/// * `hist` - The histogram of `bytes`.
+///
+/// # Returns
+///
+/// The estimate number of >=3 byte LZ matches.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::histogram::Histogram32;
+/// use lossless_transform_utils::match_estimator::estimate_num_lz_matches_fast_with_histogram;
+///
+/// let data = [0u8; 1024];
+/// let histogram = Histogram32::from_bytes(&data);
+/// assert_eq!(estimate_num_lz_matches_fast_with_histogram(&data, &histogram), 1021);
+/// ```
+pub fn estimate_num_lz_matches_fast_with_histogram(bytes: &[u8], hist: &Histogram32) -> usize {
+    let mut nonzero = hist.counter.iter().filter(|&&count| count > 0);
+    if nonzero.next().is_some() && nonzero.next().is_none() {
+        return bytes.len().saturating_sub(3);
+    }
+
+    estimate_num_lz_matches_fast(bytes)
+}
+
+/// Number of `u32` entries in the hash table used by the match estimator.
+///
+/// This is the required length of the table passed to [`estimate_num_lz_matches_fast_in`] and
+/// [`estimate_num_lz_matches_fast_with_table`].
+pub const MATCH_TABLE_LEN: usize = HASH_SIZE;
+
+/// Number of bits used to index the match estimator's hash table; `1 << MATCH_HASH_BITS` is
+/// [`MATCH_TABLE_LEN`].
+///
+/// This is the default hash table size of [`MatchEstimator`].
+pub const MATCH_HASH_BITS: usize = HASH_BITS;
+
+/// Estimates the number of >=3 byte LZ matches in a given input data stream, using a
+/// caller provided hash table.
+///
+/// This is [`estimate_num_lz_matches_fast`], without the allocation; making it usable in
+/// environments without an allocator. The table is cleared before use, so its prior contents
+/// do not matter.
+///
+/// # Arguments
+///
+/// * `bytes` - The input data stream.
+/// * `table` - Scratch space for the hash table, 128KiB in size.
+///
+/// # Returns
+///
+/// The estimate number of >=3 byte LZ matches.
+/// This number is an estimate, it is not an exact amount.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::match_estimator::{estimate_num_lz_matches_fast_in, MATCH_TABLE_LEN};
+///
+/// let mut table = vec![0u32; MATCH_TABLE_LEN];
+/// let table: &mut [u32; MATCH_TABLE_LEN] = table.as_mut_slice().try_into().unwrap();
+/// let num_matches = estimate_num_lz_matches_fast_in(b"hello world hello world hello", table);
+/// ```
+pub fn estimate_num_lz_matches_fast_in(bytes: &[u8], table: &mut [u32; MATCH_TABLE_LEN]) -> usize {
+    estimate_num_lz_matches_fast_with_table(bytes, table, true)
+}
+
+/// Estimates the number of >=3 byte LZ matches in a given input data stream, using a
+/// caller provided hash table which is only cleared on request.
+///
+/// Like [`estimate_num_lz_matches_fast_in`], this does no allocation. Pass `clear: false` to
+/// skip the 128KiB `memset` when the table is known to be zeroed already (e.g. zero initialized
+/// static memory, on first use).
+///
+/// # Arguments
+///
+/// * `bytes` - The input data stream.
+/// * `table` - Scratch space for the hash table, [`MATCH_TABLE_LEN`] entries (128KiB) in size.
+/// * `clear` - Whether to zero `table` before use. If `false`, the caller must have zeroed it;
+///   otherwise entries left over from earlier calls count as matches, as if the earlier data
+///   preceded `bytes`.
+///
+/// # Returns
+///
+/// The estimate number of >=3 byte LZ matches.
+/// This number is an estimate, it is not an exact amount.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::match_estimator::{
+///     estimate_num_lz_matches_fast, estimate_num_lz_matches_fast_with_table, MATCH_TABLE_LEN,
+/// };
+///
+/// let data = b"hello world hello world hello";
+/// let mut table = vec![0u32; MATCH_TABLE_LEN];
+/// let table: &mut [u32; MATCH_TABLE_LEN] = table.as_mut_slice().try_into().unwrap();
+///
+/// // Freshly zeroed, so no need to clear it.
+/// let num_matches = estimate_num_lz_matches_fast_with_table(data, table, false);
+/// assert_eq!(num_matches, estimate_num_lz_matches_fast(data));
+/// ```
+pub fn estimate_num_lz_matches_fast_with_table(
+    bytes: &[u8],
+    table: &mut [u32; MATCH_TABLE_LEN],
+    clear: bool,
+) -> usize {
+    if clear {
+        table.fill(0);
+    }
+    estimate_num_lz_matches_with_table(table, bytes)
+}
+
+/// Estimates the number of >=3 byte LZ matches in each of many independent blocks, in one call.
+///
+/// This is [`estimate_num_lz_matches_fast`] for every block, but with a single hash table
+/// allocated up front and cleared between blocks; amortizing the setup over all blocks.
+///
+/// # Arguments
+///
+/// * `blocks` - The independent input blocks.
+/// * `out` - Receives the estimate for each block; `out[x]` is the estimate of `blocks[x]`.
+///
+/// # Panics
+///
+/// If `out.len() != blocks.len()`.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::match_estimator::{
+///     estimate_num_lz_matches_fast, estimate_num_lz_matches_fast_batch,
+/// };
+///
+/// let blocks: [&[u8]; 2] = [&[0u8; 64], b"hello world hello world hello"];
+/// let mut out = [0; 2];
+/// estimate_num_lz_matches_fast_batch(&blocks, &mut out);
+/// assert_eq!(out[1], estimate_num_lz_matches_fast(blocks[1]));
+/// ```
+pub fn estimate_num_lz_matches_fast_batch(blocks: &[&[u8]], out: &mut [usize]) {
+    assert_eq!(
+        out.len(),
+        blocks.len(),
+        "out length must equal the number of blocks"
+    );
+
+    let mut estimator = MatchEstimator::new();
+    for (estimate, block) in out.iter_mut().zip(blocks) {
+        *estimate = estimator.estimate_num_lz_matches_fast(block);
+    }
+}
+
+/// Estimates the number of >=3 byte LZ matches per MiB (1048576 bytes) of input.
+///
+/// This is [`estimate_num_lz_matches_fast`] normalized by the input size, i.e.
+/// `matches / (bytes.len() / 2^20)`; so inputs of different sizes can be compared, e.g. on a
+/// dashboard. The estimator finds at most one match per position, so the result is at most
+/// 1048576.
+///
+/// # Returns
+///
+/// The estimated matches per MiB; 0.0 for an empty input.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::match_estimator::{
+///     estimate_lz_matches_per_mib, estimate_num_lz_matches_fast,
+/// };
+///
+/// // 1KiB is 1/1024th of a MiB.
+/// let data = [7u8; 1024];
+/// let matches = estimate_num_lz_matches_fast(&data) as f64;
+/// assert_eq!(estimate_lz_matches_per_mib(&data), matches * 1024.0);
+/// ```
+pub fn estimate_lz_matches_per_mib(bytes: &[u8]) -> f64 {
+    if bytes.is_empty() {
+        return 0.0;
+    }
+
+    let mib = bytes.len() as f64 / (1 << 20) as f64;
+    estimate_num_lz_matches_fast(bytes) as f64 / mib
+}
+
+/// Estimates the number of >=3 byte LZ matches in a given input data stream, along with a rough
+/// confidence in that estimate.
+///
+/// # Confidence Heuristic
+///
+/// The estimator remembers one 3 byte sequence per hash table slot; a sequence is forgotten
+/// when another one with the same slot comes along before it repeats, and its next repeat is
+/// then missed. How likely that is depends on how full the table got; so the confidence is
+/// `1 - load_factor`, with `load_factor` being the fraction of the [`MATCH_TABLE_LEN`] slots in
+/// use at the end of the run.
+///
+/// A nearly empty table (small inputs, or few distinct sequences) means few collisions, and a
+/// confidence near 1.0. Inputs with many distinct sequences (e.g. large, mostly unique data)
+/// saturate the table, and the estimate is then likely to undercount; the confidence drops
+/// towards 0.0. It is a heuristic signal only, not a statistical bound.
+///
+/// # Returns
+///
+/// A tuple of (the same estimate as [`estimate_num_lz_matches_fast`], confidence in
+/// `0.0..=1.0`).
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::match_estimator::estimate_num_lz_matches_with_confidence;
+///
+/// let (matches, confidence) = estimate_num_lz_matches_with_confidence(b"abcabcabcabcabc");
+/// assert!(matches > 0);
+/// assert!(confidence > 0.99);
+/// ```
+pub fn estimate_num_lz_matches_with_confidence(bytes: &[u8]) -> (usize, f64) {
+    let mut estimator = MatchEstimator::new();
+    let table = estimator.clean_table();
+    let matches = estimate_num_lz_matches_with_table_bits::<HASH_BITS>(table, bytes);
+
+    let occupied = table.iter().filter(|&&entry| entry != 0).count();
+    let load_factor = occupied as f64 / MATCH_TABLE_LEN as f64;
+    (matches, 1.0 - load_factor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::random_bytes;
+    use std::vec;
+    use std::vec::Vec;
+
+    #[test]
+    fn limited_truncates_small_budget_and_completes_large_one() {
+        let data: Vec<u8> = (0..1 << 16).map(|x| (x % 1000) as u8).collect();
+        let full = estimate_num_lz_matches_fast(&data);
+
+        let (partial, completed) = estimate_num_lz_matches_fast_limited(&data, 4096);
+        assert!(!completed);
+        // Only the first 4096 positions; of which the first 256 (before the bytes wrap) have
+        // no matches.
+        assert_eq!(partial, estimate_num_lz_matches_fast(&data[..4096 + 7]));
+        assert!(partial <= 4096 - 256 && partial < full, "got {partial}");
+
+        assert_eq!(
+            estimate_num_lz_matches_fast_limited(&data, data.len() - 7),
+            (full, true)
+        );
+        assert_eq!(
+            estimate_num_lz_matches_fast_limited(&data, usize::MAX),
+            (full, true)
+        );
+        assert_eq!(estimate_num_lz_matches_fast_limited(&data, 0), (0, false));
+    }
+
+    #[test]
+    fn caller_table_matches_allocating_estimate() {
+        let data: Vec<u8> = (0..1 << 16).map(|x| (x % 1000) as u8).collect();
+        let mut table = vec![u32::MAX; MATCH_TABLE_LEN];
+        let table: &mut [u32; MATCH_TABLE_LEN] = table.as_mut_slice().try_into().unwrap();
+
+        // Reused, dirty table must give the same result as a fresh one.
+        for _ in 0..2 {
+            assert_eq!(
+                estimate_num_lz_matches_fast_in(&data, table),
+                estimate_num_lz_matches_fast(&data)
+            );
+        }
+    }
+
+    #[test]
+    fn uncleared_table_matches_allocating_estimate_only_when_zeroed() {
+        let data: Vec<u8> = (0..1 << 16).map(|x| (x % 1000) as u8).collect();
+        let expected = estimate_num_lz_matches_fast(&data);
+        let mut table = vec![0u32; MATCH_TABLE_LEN];
+        let table: &mut [u32; MATCH_TABLE_LEN] = table.as_mut_slice().try_into().unwrap();
+
+        assert_eq!(
+            estimate_num_lz_matches_fast_with_table(&data, table, false),
+            expected
+        );
+
+        // The table now holds the data; so without clearing, the first period matches too.
+        assert!(estimate_num_lz_matches_fast_with_table(&data, table, false) > expected);
+        assert_eq!(
+            estimate_num_lz_matches_fast_with_table(&data, table, true),
+            expected
+        );
+    }
+
+    #[test]
+    fn batch_matches_per_block_estimates() {
+        let data: Vec<u8> = (0..1 << 16)
+            .map(|x| (x % 1000 * (x / 5000)) as u8)
+            .collect();
+        let blocks: Vec<&[u8]> = data.chunks(4096).chain([&[][..], &data[..5]]).collect();
+        let mut out = vec![usize::MAX; blocks.len()];
+
+        estimate_num_lz_matches_fast_batch(&blocks, &mut out);
+        for (block, &estimate) in blocks.iter().zip(&out) {
+            assert_eq!(estimate, estimate_num_lz_matches_fast(block));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn batch_rejects_mismatched_out_length() {
+        estimate_num_lz_matches_fast_batch(&[&b"abc"[..]], &mut [0; 2]);
+    }
+
+    #[test]
+    fn with_histogram_matches_plain_estimator_on_multi_symbol_input() {
+        let data: Vec<u8> = (0..1 << 16).map(|x| (x % 1000) as u8).collect();
+        let hist = Histogram32::from_bytes(&data);
+        assert_eq!(
+            estimate_num_lz_matches_fast_with_histogram(&data, &hist),
+            estimate_num_lz_matches_fast(&data)
+        );
+
+        let empty: &[u8] = &[];
+        assert_eq!(
+            estimate_num_lz_matches_fast_with_histogram(empty, &Histogram32::default()),
+            0
+        );
+    }
+
+    #[test]
+    fn with_histogram_is_analytic_on_single_symbol_input() {
+        for len in [1, 3, 4, 100, 1 << 16] {
+            let data = vec![0xAB_u8; len];
+            let hist = Histogram32::from_bytes(&data);
+            assert_eq!(
+                estimate_num_lz_matches_fast_with_histogram(&data, &hist),
+                len.saturating_sub(3)
+            );
+        }
+    }
+
+    #[test]
+    fn matches_per_mib_normalizes_by_size() {
+        let data: Vec<u8> = (0..1 << 16).map(|x| (x % 1000) as u8).collect();
+        let matches = estimate_num_lz_matches_fast(&data) as f64;
+
+        // 64KiB is 1/16th of a MiB.
+        assert_eq!(estimate_lz_matches_per_mib(&data), matches * 16.0);
+        assert_eq!(estimate_lz_matches_per_mib(&[]), 0.0);
+        assert_eq!(estimate_lz_matches_per_mib(&[1, 2, 3]), 0.0);
+    }
+
+    #[test]
+    fn confidence_drops_as_table_saturates() {
+        let small: Vec<u8> = (0..=255).collect();
+        let (matches, confidence) = estimate_num_lz_matches_with_confidence(&small);
+        assert_eq!(matches, estimate_num_lz_matches_fast(&small));
+        assert!(confidence > 0.99, "{confidence}");
+
+        // 64KiB of pseudo random data, repeated 4 times; far more distinct 3 byte sequences
+        // than table slots, so the table churns.
+        let large = random_bytes(1 << 16, 12345).repeat(4);
+        let (matches, confidence) = estimate_num_lz_matches_with_confidence(&large);
+        assert_eq!(matches, estimate_num_lz_matches_fast(&large));
+        assert!(confidence < 0.5, "{confidence}");
+    }
+}
@@ -0,0 +1,187 @@
+//! Streaming match estimator over a bounded window of the most recent bytes.
+
+use super::{hash_u32, HASH_BITS, MATCH_TABLE_LEN};
+
+/// A streaming version of the match estimator, which only counts matches within the last `N`
+/// bytes of the stream.
+///
+/// Bytes are fed in with [`WindowedMatchEstimator::push`], in pieces of any size; the result is
+/// the same however the stream is split. Everything lives in the struct (the `N` byte ring
+/// buffer) or in the caller provided hash table, so this needs neither `std` nor an allocator.
+///
+/// # Window Semantics
+///
+/// The window is the last `N` bytes pushed. Each position is checked once, when the 3rd byte of
+/// the 3 byte sequence starting at it arrives; so sequences spanning two calls to `push` are
+/// handled like any other. A position counts as a match if the same 3 bytes also start at an
+/// earlier position which is still fully inside the window, i.e. at most `N - 3` bytes before
+/// it. Older occurrences have slid out of the window, and no longer count.
+///
+/// Unlike [`estimate_num_lz_matches_fast`], every position is checked one at a time, including
+/// the last few; and the table stores positions, which are verified against the window, so
+/// colliding sequences never produce false matches. Collisions may still hide matches, as with
+/// the other estimators.
+///
+/// # Example
+///
+/// ```
+/// use lossless_transform_utils::match_estimator::{WindowedMatchEstimator, MATCH_TABLE_LEN};
+///
+/// let mut table = vec![0u32; MATCH_TABLE_LEN];
+/// let table: &mut [u32; MATCH_TABLE_LEN] = table.as_mut_slice().try_into().unwrap();
+///
+/// let mut estimator = WindowedMatchEstimator::<64>::new(table);
+/// assert_eq!(estimator.push(b"abcd"), 0);
+/// // Both new "abc"s repeat an earlier one; "cda", "dab", "bca" and "cab" are new.
+/// assert_eq!(estimator.push(b"abcabc"), 2);
+/// ```
+///
+/// [`estimate_num_lz_matches_fast`]: super::estimate_num_lz_matches_fast
+pub struct WindowedMatchEstimator<'a, const N: usize> {
+    table: &'a mut [u32; MATCH_TABLE_LEN],
+    window: [u8; N],
+    /// Number of bytes pushed so far; the next byte goes to `window[pushed % N]`.
+    pushed: u64,
+    /// The last 3 bytes pushed, as a little endian value; the oldest byte is the lowest.
+    recent: u32,
+}
+
+impl<'a, const N: usize> WindowedMatchEstimator<'a, N> {
+    /// Creates a new estimator with an empty window.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - Scratch space for the hash table, 128KiB in size. Cleared here, so its prior
+    ///   contents do not matter. Borrowed for as long as the estimator lives.
+    ///
+    /// # Panics
+    ///
+    /// If `N` is less than 3, or more than [`u32::MAX`]; positions are stored as [`u32`].
+    pub fn new(table: &'a mut [u32; MATCH_TABLE_LEN]) -> Self {
+        assert!(
+            (3..=u32::MAX as usize).contains(&N),
+            "window must be between 3 and u32::MAX bytes"
+        );
+
+        table.fill(0);
+        Self {
+            table,
+            window: [0; N],
+            pushed: 0,
+            recent: 0,
+        }
+    }
+
+    /// Appends `bytes` to the stream, sliding the window forward.
+    ///
+    /// # Returns
+    ///
+    /// The number of new matches; i.e. how many of the positions completed by `bytes` repeat a
+    /// 3 byte sequence still within the window.
+    pub fn push(&mut self, bytes: &[u8]) -> usize {
+        let mut matches = 0;
+        for &byte in bytes {
+            self.window[(self.pushed % N as u64) as usize] = byte;
+            self.pushed += 1;
+            self.recent = (self.recent >> 8) | ((byte as u32) << 16);
+
+            if self.pushed >= 3 {
+                matches += self.probe_and_insert(self.pushed - 3) as usize;
+            }
+        }
+        matches
+    }
+
+    /// Checks the sequence at `position` (stored in `recent`) against the table, then replaces
+    /// the table's entry with it.
+    #[inline(always)]
+    fn probe_and_insert(&mut self, position: u64) -> bool {
+        let index = (hash_u32(self.recent) >> (32 - HASH_BITS)) as usize;
+
+        // Entries store `position + 1`, so 0 means empty. Only the low 32 bits are kept; the
+        // distance is correct as long as the earlier position is within the window.
+        let entry = self.table[index];
+        let distance = (position as u32).wrapping_sub(entry.wrapping_sub(1)) as u64;
+        let is_match = entry != 0
+            && distance != 0
+            && distance <= (N - 3) as u64
+            && self.sequence_at(position - distance) == self.recent;
+
+        self.table[index] = (position as u32).wrapping_add(1);
+        is_match
+    }
+
+    /// Reads the 3 byte sequence starting at `position`, which must still be in the window.
+    #[inline(always)]
+    fn sequence_at(&self, position: u64) -> u32 {
+        let byte = |offset: u64| self.window[((position + offset) % N as u64) as usize] as u32;
+        byte(0) | (byte(1) << 8) | (byte(2) << 16)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::match_estimator::estimate_num_lz_matches_fast;
+    use std::boxed::Box;
+    use std::vec;
+    use std::vec::Vec;
+
+    fn new_table() -> Box<[u32; MATCH_TABLE_LEN]> {
+        vec![0u32; MATCH_TABLE_LEN]
+            .into_boxed_slice()
+            .try_into()
+            .unwrap()
+    }
+
+    #[test]
+    fn windowed_run_matches_one_shot_over_window_contents() {
+        // Period of 100 distinct non-zero bytes; so no 3 byte sequence repeats within 4 bytes
+        // (which the one-shot estimator misses). Ends in bytes which never occurred before, so
+        // the tail positions the one-shot estimator skips have no matches either.
+        let mut data: Vec<u8> = (0..4000).map(|x| (x % 100 + 1) as u8).collect();
+        data.extend_from_slice(&[200, 201, 202, 203, 204, 205, 206, 207]);
+
+        let mut table = new_table();
+        let mut estimator = WindowedMatchEstimator::<4096>::new(&mut table);
+        let matches: usize = data.chunks(37).map(|chunk| estimator.push(chunk)).sum();
+
+        assert_eq!(matches, estimate_num_lz_matches_fast(&data));
+        // Every periodic position after the first period; the last 2 include the new bytes.
+        assert_eq!(matches, 4000 - 2 - 100);
+    }
+
+    #[test]
+    fn matches_slide_out_of_window() {
+        let filler: Vec<u8> = (1..=100).collect();
+
+        // "xyz" is 3 + 100 bytes back; inside a 128 byte window, but not a 64 byte one.
+        let mut table = new_table();
+        let mut large = WindowedMatchEstimator::<128>::new(&mut table);
+        large.push(b"xyz");
+        large.push(&filler);
+        assert_eq!(large.push(b"xyz"), 1);
+
+        let mut table = new_table();
+        let mut small = WindowedMatchEstimator::<64>::new(&mut table);
+        small.push(b"xyz");
+        small.push(&filler);
+        assert_eq!(small.push(b"xyz"), 0);
+    }
+
+    #[test]
+    fn sequences_spanning_pushes_and_window_edge() {
+        // The repeat is exactly `N - 3` bytes back; the earliest position still in the window.
+        let mut table = new_table();
+        let mut estimator = WindowedMatchEstimator::<8>::new(&mut table);
+        assert_eq!(estimator.push(b"ab"), 0);
+        assert_eq!(estimator.push(b"cdea"), 0);
+        assert_eq!(estimator.push(b"b"), 0);
+        assert_eq!(estimator.push(b"c"), 1);
+
+        // One byte further back, and it's gone.
+        let mut table = new_table();
+        let mut estimator = WindowedMatchEstimator::<8>::new(&mut table);
+        assert_eq!(estimator.push(b"abcdefabc"), 0);
+    }
+}
@@ -0,0 +1,77 @@
+//! Floating point functions which `core` does not provide.
+//!
+//! With the `std` feature, these forward to the [`f64`]/[`f32`] methods from `std`, which use
+//! the platform's math library. Without it, they forward to the pure Rust [`libm`] crate; so the
+//! crate still builds (and gives the same results, give or take the last bit) in `no_std`.
+
+/// Base 2 logarithm of `x`.
+#[inline(always)]
+pub(crate) fn log2(x: f64) -> f64 {
+    #[cfg(feature = "std")]
+    return x.log2();
+    #[cfg(not(feature = "std"))]
+    return libm::log2(x);
+}
+
+/// Base 2 logarithm of `x`, in single precision.
+#[inline(always)]
+pub(crate) fn log2f(x: f32) -> f32 {
+    #[cfg(feature = "std")]
+    return x.log2();
+    #[cfg(not(feature = "std"))]
+    return libm::log2f(x);
+}
+
+/// Natural logarithm of `x`.
+#[inline(always)]
+pub(crate) fn ln(x: f64) -> f64 {
+    #[cfg(feature = "std")]
+    return x.ln();
+    #[cfg(not(feature = "std"))]
+    return libm::log(x);
+}
+
+/// `2` raised to the power of `x`.
+#[inline(always)]
+pub(crate) fn exp2(x: f64) -> f64 {
+    #[cfg(feature = "std")]
+    return x.exp2();
+    #[cfg(not(feature = "std"))]
+    return libm::exp2(x);
+}
+
+/// Square root of `x`.
+#[inline(always)]
+pub(crate) fn sqrt(x: f64) -> f64 {
+    #[cfg(feature = "std")]
+    return x.sqrt();
+    #[cfg(not(feature = "std"))]
+    return libm::sqrt(x);
+}
+
+/// Largest integer less than or equal to `x`.
+#[inline(always)]
+pub(crate) fn floor(x: f64) -> f64 {
+    #[cfg(feature = "std")]
+    return x.floor();
+    #[cfg(not(feature = "std"))]
+    return libm::floor(x);
+}
+
+/// Smallest integer greater than or equal to `x`.
+#[inline(always)]
+pub(crate) fn ceil(x: f64) -> f64 {
+    #[cfg(feature = "std")]
+    return x.ceil();
+    #[cfg(not(feature = "std"))]
+    return libm::ceil(x);
+}
+
+/// Nearest integer to `x`, rounding half way cases away from zero.
+#[inline(always)]
+pub(crate) fn round(x: f64) -> f64 {
+    #[cfg(feature = "std")]
+    return x.round();
+    #[cfg(not(feature = "std"))]
+    return libm::round(x);
+}
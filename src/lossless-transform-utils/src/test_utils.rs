@@ -0,0 +1,17 @@
+//! Helpers shared by the crate's unit tests.
+
+use std::vec::Vec;
+
+/// Generates `len` pseudo random bytes, reproducible from `seed`.
+///
+/// Each byte is the top 8 bits of a linear congruential generator's state; the low bits of an
+/// LCG repeat with a short period, so they're not used.
+pub(crate) fn random_bytes(len: usize, seed: u32) -> Vec<u8> {
+    let mut state = seed;
+    (0..len)
+        .map(|_| {
+            state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+            (state >> 24) as u8
+        })
+        .collect()
+}
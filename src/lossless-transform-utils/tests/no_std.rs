@@ -0,0 +1,33 @@
+//! Runs the core pipeline (histogram, entropy, match estimation) from a `#![no_std]` crate.
+//!
+//! Run with `cargo test --no-default-features --test no_std` to check the library itself builds
+//! and works without `std`; i.e. that no hot path relies on `std` only feature detection.
+//! (The test harness links `std`, but this crate and the library don't use it.)
+
+#![no_std]
+
+use lossless_transform_utils::entropy::code_length_of_histogram32;
+use lossless_transform_utils::histogram::{histogram32_from_bytes, Histogram32};
+use lossless_transform_utils::match_estimator::estimate_num_lz_matches_fast;
+
+/// Large enough for the batched histogram implementation; repeats every 251 bytes.
+fn data() -> [u8; 8192] {
+    core::array::from_fn(|x| (x % 251) as u8)
+}
+
+#[test]
+fn pipeline_runs_without_std() {
+    let data = data();
+
+    let mut histogram = Histogram32::default();
+    histogram32_from_bytes(&data, &mut histogram);
+    assert_eq!(histogram.counter[0], 33);
+    assert_eq!(histogram.counter[250], 32);
+
+    let bits_per_byte = code_length_of_histogram32(&histogram, data.len() as u64);
+    assert!((7.9..8.0).contains(&bits_per_byte), "got {bits_per_byte}");
+
+    // Everything after the first period is a match.
+    let matches = estimate_num_lz_matches_fast(&data);
+    assert!(matches > data.len() * 9 / 10, "got {matches}");
+}